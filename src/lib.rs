@@ -0,0 +1,279 @@
+//! Library interface for giallo.kak's highlighting engine.
+//!
+//! The `giallo-kak` binary drives this same `giallo::Registry` pipeline
+//! over a FIFO/stdio protocol, serializing straight to Kakoune command
+//! strings. This crate exposes the pipeline as a plain Rust API instead:
+//! [`Highlighter::highlight`] returns typed [`HighlightRange`]s and
+//! [`FaceDefinition`]s, and [`HighlightOutput::to_kakoune_commands`]
+//! recovers the serialized string form when you do need it. This lets
+//! other Rust tools embed giallo.kak's highlighting without shelling out
+//! to the binary, and lets tests assert on structured data instead of
+//! string-scraping `set-option` lines.
+//!
+//! The binary predates this crate and isn't migrated onto it yet — it still
+//! builds its own (richer) command strings directly, including dual
+//! light/dark theme support and `[highlight]`-config-aware granularity this
+//! crate doesn't yet expose. What it no longer does is redefine the style →
+//! face-spec computation itself: [`normalize_hex`], [`StyleKey`],
+//! [`style_key`], [`strip_hash`], and [`style_to_face_spec`] are the one
+//! implementation of that, and `src/main.rs` calls into it rather than
+//! keeping its own divergent copy.
+
+use std::collections::HashMap;
+
+use giallo::{HighlightOptions, HighlightedCode, Registry, ThemeVariant, PLAIN_GRAMMAR_NAME};
+
+const DEFAULT_THEME: &str = "catppuccin-frappe";
+
+/// Minimal configuration a [`Highlighter`] needs to resolve a theme when
+/// the caller doesn't pass one explicitly.
+#[derive(Clone, Debug, Default)]
+pub struct HighlighterConfig {
+    pub default_theme: Option<String>,
+}
+
+impl HighlighterConfig {
+    fn resolve_theme<'a>(&'a self, theme: &'a str) -> &'a str {
+        if theme.is_empty() {
+            self.default_theme.as_deref().unwrap_or(DEFAULT_THEME)
+        } else {
+            theme
+        }
+    }
+}
+
+/// One highlighted span of source text, as an inclusive `[start, end]`
+/// 1-indexed `(line, column)` range (`end_col` is the span's last byte,
+/// as in a Kakoune range) and the face name it should be
+/// painted with — the typed equivalent of a single
+/// `"{line}.{col},{line}.{col}|{face}"` range entry.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HighlightRange {
+    pub start_line: usize,
+    pub start_col: usize,
+    pub end_line: usize,
+    pub end_col: usize,
+    pub face: String,
+}
+
+/// One named Kakoune face definition, e.g. `name: "giallo_0001"`,
+/// `spec: "rgb:ff0000,default+b"`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FaceDefinition {
+    pub name: String,
+    pub spec: String,
+}
+
+/// The structured result of highlighting a buffer: every span plus the
+/// face definitions they reference.
+#[derive(Clone, Debug, Default)]
+pub struct HighlightOutput {
+    pub ranges: Vec<HighlightRange>,
+    pub faces: Vec<FaceDefinition>,
+}
+
+impl HighlightOutput {
+    /// Render as the same `set-face`/`set-option buffer giallo_hl_ranges`
+    /// command string the FIFO protocol writes back to Kakoune.
+    pub fn to_kakoune_commands(&self) -> String {
+        let mut commands = String::new();
+        for face in &self.faces {
+            commands.push_str(&format!("set-face buffer {} {}\n", face.name, face.spec));
+        }
+
+        commands.push_str("set-option buffer giallo_hl_ranges %val{timestamp}");
+        for range in &self.ranges {
+            commands.push_str(&format!(
+                " {}.{},{}.{}|{}",
+                range.start_line, range.start_col, range.end_line, range.end_col, range.face
+            ));
+        }
+        commands.push('\n');
+        commands
+    }
+}
+
+/// Collapse an 8-digit `#rrggbbaa` hex color down to `#rrggbb` by dropping
+/// the alpha channel; Kakoune face specs have no alpha component. Colors
+/// without an alpha channel pass through unchanged.
+///
+/// `pub` (not crate-private) so `src/main.rs` can call the same
+/// implementation instead of keeping its own copy — see this crate's
+/// top-level doc comment.
+pub fn normalize_hex(hex: &str) -> String {
+    if hex.len() == 9 {
+        hex[..7].to_string()
+    } else {
+        hex.to_string()
+    }
+}
+
+/// A [`giallo::Style`]'s resolved appearance, flattened into a hashable key
+/// so `highlight_output_from` (and `src/main.rs`'s equivalent builders) can
+/// dedupe identical styles into one face definition instead of emitting a
+/// new one per token.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct StyleKey {
+    pub fg: String,
+    pub bg: String,
+    pub bold: bool,
+    pub italic: bool,
+    pub underline: bool,
+    pub strike: bool,
+}
+
+/// Build the [`StyleKey`] for `style`.
+pub fn style_key(style: &giallo::Style) -> StyleKey {
+    StyleKey {
+        fg: normalize_hex(&style.foreground.as_hex()),
+        bg: normalize_hex(&style.background.as_hex()),
+        bold: style.font_style.contains(giallo::FontStyle::BOLD),
+        italic: style.font_style.contains(giallo::FontStyle::ITALIC),
+        underline: style.font_style.contains(giallo::FontStyle::UNDERLINE),
+        strike: style.font_style.contains(giallo::FontStyle::STRIKETHROUGH),
+    }
+}
+
+/// Drop a leading `#` from a hex color, if present.
+pub fn strip_hash(hex: &str) -> &str {
+    hex.strip_prefix('#').unwrap_or(hex)
+}
+
+/// Render `style` as a Kakoune face spec (`"rgb:{fg},{bg}[+{attrs}]"`),
+/// collapsing the background onto `"default"` when it matches `default_bg`
+/// so the face preserves terminal transparency instead of hard-coding the
+/// theme's own background color.
+pub fn style_to_face_spec(style: &giallo::Style, default_bg: &str) -> String {
+    let mut attrs = String::new();
+    if style.font_style.contains(giallo::FontStyle::BOLD) {
+        attrs.push('b');
+    }
+    if style.font_style.contains(giallo::FontStyle::ITALIC) {
+        attrs.push('i');
+    }
+    if style.font_style.contains(giallo::FontStyle::UNDERLINE) {
+        attrs.push('u');
+    }
+    if style.font_style.contains(giallo::FontStyle::STRIKETHROUGH) {
+        attrs.push('s');
+    }
+
+    let fg = strip_hash(&normalize_hex(&style.foreground.as_hex())).to_string();
+    let bg = strip_hash(&normalize_hex(&style.background.as_hex())).to_string();
+
+    let bg_spec = if strip_hash(default_bg) == bg {
+        String::from("default")
+    } else {
+        format!("rgb:{bg}")
+    };
+
+    if attrs.is_empty() {
+        format!("rgb:{fg},{bg_spec}")
+    } else {
+        format!("rgb:{fg},{bg_spec}+{attrs}")
+    }
+}
+
+/// Walk `highlighted`'s tokens, deduplicating styles into face definitions
+/// (`"giallo_NNNN"`, or `"default"` for the theme's own default style) and
+/// producing one [`HighlightRange`] per non-empty token. Only `Single`
+/// themes are supported; a `Dual` theme's light variant is used, matching
+/// the binary's own fallback when a Dual highlight's light face is asked
+/// for standalone.
+fn highlight_output_from(highlighted: &HighlightedCode<'_>) -> HighlightOutput {
+    let theme = match highlighted.theme {
+        ThemeVariant::Single(theme) => theme,
+        ThemeVariant::Dual { light, .. } => light,
+    };
+    let default_style = theme.default_style;
+    let default_bg = default_style.background.as_hex();
+
+    let mut faces = Vec::new();
+    let mut face_map: HashMap<StyleKey, String> = HashMap::new();
+    let mut face_counter = 0usize;
+    let mut ranges = Vec::new();
+
+    for (line_idx, line_tokens) in highlighted.tokens.iter().enumerate() {
+        let mut col = 0usize;
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let bytes = token.text.as_bytes().len();
+            let start = col;
+            let end_excl = col + bytes;
+            col = end_excl;
+
+            let style = match token.style {
+                ThemeVariant::Single(style) => style,
+                ThemeVariant::Dual { light, .. } => light,
+            };
+
+            let face_name = if style == default_style {
+                "default".to_string()
+            } else {
+                let key = style_key(&style);
+                if let Some(name) = face_map.get(&key) {
+                    name.clone()
+                } else {
+                    face_counter += 1;
+                    let name = format!("giallo_{face_counter:04}");
+                    faces.push(FaceDefinition {
+                        name: name.clone(),
+                        spec: style_to_face_spec(&style, &default_bg),
+                    });
+                    face_map.insert(key, name.clone());
+                    name
+                }
+            };
+
+            let line = line_idx + 1;
+            ranges.push(HighlightRange {
+                start_line: line,
+                start_col: start + 1,
+                end_line: line,
+                end_col: end_excl.max(1),
+                face: face_name,
+            });
+        }
+    }
+
+    HighlightOutput { ranges, faces }
+}
+
+/// Stable embeddable entry point: wraps a `giallo::Registry` plus a small
+/// amount of theme-resolution config, and highlights source text into
+/// typed ranges rather than a serialized command string.
+pub struct Highlighter {
+    registry: Registry,
+    config: HighlighterConfig,
+}
+
+impl Highlighter {
+    pub fn new(registry: Registry, config: HighlighterConfig) -> Self {
+        Self { registry, config }
+    }
+
+    /// Highlight `code` for `lang` with `theme` (an empty string defers to
+    /// `config.default_theme`, then the built-in default). Falls back to
+    /// the plain grammar if `lang` fails to highlight, mirroring the
+    /// binary's `H` command handler.
+    pub fn highlight(&self, lang: &str, theme: &str, code: &str) -> Result<HighlightOutput, String> {
+        let resolved_theme = self.config.resolve_theme(theme);
+        let options = HighlightOptions::new(lang, ThemeVariant::Single(resolved_theme));
+
+        let highlighted = match self.registry.highlight(code, &options) {
+            Ok(highlighted) => highlighted,
+            Err(_) => {
+                let fallback =
+                    HighlightOptions::new(PLAIN_GRAMMAR_NAME, ThemeVariant::Single(resolved_theme));
+                self.registry
+                    .highlight(code, &fallback)
+                    .map_err(|err| err.to_string())?
+            }
+        };
+
+        Ok(highlight_output_from(&highlighted))
+    }
+}
@@ -4,27 +4,18 @@ use std::io::{self, BufRead, Read, Write};
 use std::path::{Path, PathBuf};
 use std::process;
 use std::process::{Command, Stdio};
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
 use std::sync::mpsc::{channel, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread;
 
 use giallo::{HighlightOptions, Registry, ThemeVariant, PLAIN_GRAMMAR_NAME};
+use giallo_kak::{normalize_hex, strip_hash, style_key, style_to_face_spec, StyleKey};
 use log;
 use serde::Deserialize;
 
 mod server_resources;
-use server_resources::ServerResources;
-
-#[derive(Clone, Debug, PartialEq, Eq, Hash)]
-struct StyleKey {
-    fg: String,
-    bg: String,
-    bold: bool,
-    italic: bool,
-    underline: bool,
-    strike: bool,
-}
+use server_resources::{CrawlEntry, ServerResources, WorkerHandles, WorkerState};
 
 #[derive(Clone, Debug)]
 struct FaceDef {
@@ -32,6 +23,15 @@ struct FaceDef {
     spec: String,
 }
 
+/// The last full-buffer highlight computed for a buffer, kept so a
+/// viewport-only refresh (same content, different visible window) can
+/// resend a filtered slice of `ranges` without re-parsing or re-highlighting.
+#[derive(Clone, Debug)]
+struct BufferHighlightCache {
+    content_hash: u64,
+    ranges: String,
+}
+
 #[derive(Clone, Debug)]
 struct BufferContext {
     session: String,
@@ -39,6 +39,29 @@ struct BufferContext {
     sentinel: String,
     lang: Arc<Mutex<String>>,
     theme: Arc<Mutex<String>>,
+    file_path: Option<PathBuf>,
+    highlight_cache: Arc<Mutex<Option<BufferHighlightCache>>>,
+    /// The `(light, dark)` theme names behind an `auto:light,dark` theme, so
+    /// a later plain `SET_THEME buffer auto` can re-resolve without the
+    /// caller repeating the pair.
+    auto_theme_pair: Arc<Mutex<Option<(String, String)>>>,
+    /// The most recent buffer text handed to `highlight_and_send`, kept so a
+    /// `SET_LANG`/`SET_THEME` can re-highlight immediately instead of waiting
+    /// for the next FIFO write from Kakoune.
+    last_text: Arc<Mutex<Option<String>>>,
+    /// Bumped every time a new buffer snapshot is queued for this buffer.
+    /// A highlight pass stamps the generation it started with and checks it
+    /// again before `send_to_kak`, so output computed against a snapshot
+    /// that's since been superseded by a newer edit is discarded rather than
+    /// clobbering the more recent highlight.
+    generation: Arc<AtomicU64>,
+    /// A cached, already-open handle onto this session's Kakoune command
+    /// FIFO (what `kak -p <session>` itself writes to), reused by
+    /// `send_to_kak` across every highlight update instead of spawning a
+    /// fresh `kak -p` process each time. `None` until the first successful
+    /// send, and cleared so the next call reopens from scratch if a write
+    /// ever fails (e.g. the session restarted and recreated the FIFO).
+    kak_pipe: Arc<Mutex<Option<fs::File>>>,
 }
 
 impl BufferContext {
@@ -49,81 +72,148 @@ impl BufferContext {
             sentinel,
             lang: Arc::new(Mutex::new(lang)),
             theme: Arc::new(Mutex::new(theme)),
+            file_path: None,
+            highlight_cache: Arc::new(Mutex::new(None)),
+            auto_theme_pair: Arc::new(Mutex::new(None)),
+            last_text: Arc::new(Mutex::new(None)),
+            generation: Arc::new(AtomicU64::new(0)),
+            kak_pipe: Arc::new(Mutex::new(None)),
         }
     }
-}
 
-fn normalize_hex(hex: &str) -> String {
-    if hex.len() == 9 {
-        hex[..7].to_string()
-    } else {
-        hex.to_string()
+    fn with_file_path(mut self, file_path: Option<String>) -> Self {
+        self.file_path = file_path.filter(|p| !p.is_empty()).map(PathBuf::from);
+        self
     }
-}
 
-fn style_key(style: &giallo::Style) -> StyleKey {
-    StyleKey {
-        fg: normalize_hex(&style.foreground.as_hex()),
-        bg: normalize_hex(&style.background.as_hex()),
-        bold: style.font_style.contains(giallo::FontStyle::BOLD),
-        italic: style.font_style.contains(giallo::FontStyle::ITALIC),
-        underline: style.font_style.contains(giallo::FontStyle::UNDERLINE),
-        strike: style.font_style.contains(giallo::FontStyle::STRIKETHROUGH),
+    fn with_auto_theme_pair(self, pair: Option<(String, String)>) -> Self {
+        if let Some(pair) = pair {
+            *self.auto_theme_pair.lock().unwrap() = Some(pair);
+        }
+        self
     }
 }
 
-fn strip_hash(hex: &str) -> &str {
-    if hex.starts_with('#') {
-        &hex[1..]
-    } else {
-        hex
+/// Literal punctuation characters recognized by `HighlightConfig`'s
+/// `punctuation`/`specialize` toggles. Checked against a token's full text,
+/// not a capture name (see `HighlightConfig`'s doc comment for why).
+const PUNCTUATION_TOKENS: &[&str] = &[",", ";", "(", ")", "[", "]", "{", "}"];
+
+/// Literal operator substrings recognized by `HighlightConfig`'s
+/// `operators`/`specialize` toggles, same caveat as `PUNCTUATION_TOKENS`.
+/// `.`/`::`/`->` are listed first so `specialize` can give them their own
+/// faces distinct from the generic operator bucket.
+const OPERATOR_TOKENS: &[&str] = &[
+    ".", "::", "->", "=>", "==", "!=", "<=", ">=", "&&", "||", "+", "-", "*", "/", "%", "=", "<",
+    ">", "!", "&", "|", "^",
+];
+
+/// A token is treated as a string literal if its full text is wrapped in a
+/// matching pair of quote characters. This only catches grammars that emit
+/// a whole simple string literal as one token; interpolated or multi-token
+/// strings (f-strings, template literals with `${}`) aren't recognized,
+/// since that split is a capture-level decision made inside the external
+/// `giallo` crate, not something visible in the token text alone.
+fn is_quoted_string_token(text: &str) -> bool {
+    let bytes = text.as_bytes();
+    if bytes.len() < 2 {
+        return false;
     }
+    let first = bytes[0];
+    matches!(first, b'"' | b'\'' | b'`') && bytes[bytes.len() - 1] == first
 }
 
-fn style_to_face_spec(style: &giallo::Style, default_bg: Option<&str>) -> String {
-    let mut attrs = String::new();
-    if style.font_style.contains(giallo::FontStyle::BOLD) {
-        attrs.push('b');
-    }
-    if style.font_style.contains(giallo::FontStyle::ITALIC) {
-        attrs.push('i');
-    }
-    if style.font_style.contains(giallo::FontStyle::UNDERLINE) {
-        attrs.push('u');
+/// Apply `HighlightConfig`'s category toggles to one token's resolved face
+/// name: disabled categories fall back to `"default"`, and `specialize`
+/// (when enabled) swaps recognized punctuation/operator tokens onto their
+/// own dedicated faces (see `specialized_highlight_faces`) instead of the
+/// theme's generic scope face.
+fn apply_highlight_granularity(text: &str, face_name: String, config: &Config) -> String {
+    if !config.highlight.strings.unwrap_or(true) && is_quoted_string_token(text) {
+        return "default".to_string();
     }
-    if style.font_style.contains(giallo::FontStyle::STRIKETHROUGH) {
-        attrs.push('s');
+
+    if PUNCTUATION_TOKENS.contains(&text) {
+        if !config.highlight.punctuation.unwrap_or(true) {
+            return "default".to_string();
+        }
+        if config.highlight.specialize.unwrap_or(false) {
+            return "giallo_punctuation".to_string();
+        }
+    } else if OPERATOR_TOKENS.contains(&text) {
+        if !config.highlight.operators.unwrap_or(true) {
+            return "default".to_string();
+        }
+        if config.highlight.specialize.unwrap_or(false) {
+            return match text {
+                "." => "giallo_operator_dot",
+                "::" => "giallo_operator_path",
+                "->" => "giallo_operator_arrow",
+                _ => "giallo_operator",
+            }
+            .to_string();
+        }
     }
 
-    let fg_hex = normalize_hex(&style.foreground.as_hex());
-    let bg_hex = normalize_hex(&style.background.as_hex());
-    let fg = strip_hash(&fg_hex);
-    let bg = strip_hash(&bg_hex);
+    face_name
+}
 
-    // If background matches default theme background, use "default" to preserve terminal transparency
-    let bg_spec = if let Some(default_bg_hex) = default_bg {
-        if strip_hash(default_bg_hex) == bg {
-            String::from("default")
-        } else {
-            format!("rgb:{bg}")
-        }
-    } else {
-        format!("rgb:{bg}")
-    };
+/// `set-face` definitions for the faces `apply_highlight_granularity` can
+/// assign under `specialize = true`, aliased (via `ref:`) onto Kakoune's own
+/// standard `operator`/`punctuation`/`module` faces so they pick up
+/// whatever the active colorscheme already defines for those scopes rather
+/// than inventing new colors.
+fn specialized_highlight_faces() -> Vec<FaceDef> {
+    [
+        ("giallo_punctuation", "punctuation"),
+        ("giallo_operator", "operator"),
+        ("giallo_operator_dot", "operator"),
+        ("giallo_operator_path", "module"),
+        ("giallo_operator_arrow", "operator"),
+    ]
+    .into_iter()
+    .map(|(name, target)| FaceDef {
+        name: name.to_string(),
+        spec: format!("ref:{target}"),
+    })
+    .collect()
+}
 
-    if attrs.is_empty() {
-        format!("rgb:{fg},{bg_spec}")
-    } else {
-        format!("rgb:{fg},{bg_spec}+{attrs}")
+/// Build the `set-face`/range commands for a highlighted buffer. A `Single`
+/// theme produces one face per distinct style, as before. A `Dual` theme
+/// produces a `giallo_light_*`/`giallo_dark_*` pair per distinct style and a
+/// generated snippet (the third tuple element) that aliases the logical
+/// `giallo_NNNN` face used by the ranges to whichever variant is active.
+/// `config`'s `[highlight]` section (see `HighlightConfig`) is applied to
+/// every token's face before it's serialized.
+fn build_kakoune_commands(
+    highlighted: &giallo::HighlightedCode<'_>,
+    config: &Config,
+) -> (Vec<FaceDef>, String, Option<String>) {
+    match highlighted.theme {
+        ThemeVariant::Single(theme) => {
+            let (mut faces, ranges) = build_single_theme_faces(highlighted, theme, config);
+            if config.highlight.specialize.unwrap_or(false) {
+                faces.extend(specialized_highlight_faces());
+            }
+            (faces, ranges, None)
+        }
+        ThemeVariant::Dual { light, dark } => {
+            let (mut faces, ranges, face_count) =
+                build_dual_theme_faces(highlighted, light, dark, config);
+            if config.highlight.specialize.unwrap_or(false) {
+                faces.extend(specialized_highlight_faces());
+            }
+            (faces, ranges, Some(build_theme_variant_snippet(face_count)))
+        }
     }
 }
 
-fn build_kakoune_commands(highlighted: &giallo::HighlightedCode<'_>) -> (Vec<FaceDef>, String) {
-    let theme = match highlighted.theme {
-        ThemeVariant::Single(theme) => theme,
-        ThemeVariant::Dual { light, .. } => light,
-    };
-
+fn build_single_theme_faces(
+    highlighted: &giallo::HighlightedCode<'_>,
+    theme: &giallo::Theme,
+    config: &Config,
+) -> (Vec<FaceDef>, String) {
     let default_style = theme.default_style;
     let default_bg = default_style.background.as_hex();
 
@@ -158,7 +248,7 @@ fn build_kakoune_commands(highlighted: &giallo::HighlightedCode<'_>) -> (Vec<Fac
                 } else {
                     face_counter += 1;
                     let name = format!("giallo_{face_counter:04}");
-                    let spec = style_to_face_spec(&style, Some(&default_bg));
+                    let spec = style_to_face_spec(&style, &default_bg);
                     faces.push(FaceDef {
                         name: name.clone(),
                         spec,
@@ -168,6 +258,8 @@ fn build_kakoune_commands(highlighted: &giallo::HighlightedCode<'_>) -> (Vec<Fac
                 }
             };
 
+            let face_name = apply_highlight_granularity(token.text, face_name, config);
+
             let line = line_idx + 1;
             let col_start = start + 1;
             let col_end = end_excl.max(1);
@@ -185,6 +277,557 @@ fn build_kakoune_commands(highlighted: &giallo::HighlightedCode<'_>) -> (Vec<Fac
     (faces, ranges_str)
 }
 
+/// Same range computation as [`build_single_theme_faces`], but keyed on the
+/// `(light, dark)` style pair so both variants share one logical face name.
+/// Returns the light/dark `FaceDef`s, the ranges string, and the number of
+/// distinct logical faces so the caller can build the alias snippet.
+fn build_dual_theme_faces(
+    highlighted: &giallo::HighlightedCode<'_>,
+    light: &giallo::Theme,
+    dark: &giallo::Theme,
+    config: &Config,
+) -> (Vec<FaceDef>, String, usize) {
+    let light_default = light.default_style;
+    let dark_default = dark.default_style;
+    let light_default_bg = light_default.background.as_hex();
+    let dark_default_bg = dark_default.background.as_hex();
+
+    let mut faces: Vec<FaceDef> = Vec::new();
+    let mut face_map: HashMap<(StyleKey, StyleKey), String> = HashMap::new();
+    let mut face_counter = 0usize;
+
+    let mut ranges: Vec<String> = Vec::new();
+
+    for (line_idx, line_tokens) in highlighted.tokens.iter().enumerate() {
+        let mut col = 0usize;
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+
+            let bytes = token.text.as_bytes().len();
+            let start = col;
+            let end_excl = col + bytes;
+            col = end_excl;
+
+            let ThemeVariant::Dual {
+                light: light_style,
+                dark: dark_style,
+            } = token.style
+            else {
+                continue;
+            };
+
+            let face_name = if light_style == light_default && dark_style == dark_default {
+                "default".to_string()
+            } else {
+                let key = (style_key(&light_style), style_key(&dark_style));
+                if let Some(name) = face_map.get(&key) {
+                    name.clone()
+                } else {
+                    face_counter += 1;
+                    let name = format!("giallo_{face_counter:04}");
+                    faces.push(FaceDef {
+                        name: format!("giallo_light_{face_counter:04}"),
+                        spec: style_to_face_spec(&light_style, &light_default_bg),
+                    });
+                    faces.push(FaceDef {
+                        name: format!("giallo_dark_{face_counter:04}"),
+                        spec: style_to_face_spec(&dark_style, &dark_default_bg),
+                    });
+                    face_map.insert(key, name.clone());
+                    name
+                }
+            };
+
+            let face_name = apply_highlight_granularity(token.text, face_name, config);
+
+            let line = line_idx + 1;
+            let col_start = start + 1;
+            let col_end = end_excl.max(1);
+
+            ranges.push(format!("{line}.{col_start},{line}.{col_end}|{face_name}"));
+        }
+    }
+
+    let ranges_str = if ranges.is_empty() {
+        String::new()
+    } else {
+        ranges.join(" ")
+    };
+
+    (faces, ranges_str, face_counter)
+}
+
+/// Kakoune snippet that aliases each logical `giallo_NNNN` face to its
+/// `giallo_light_NNNN`/`giallo_dark_NNNN` counterpart based on the
+/// `giallo_background` option, which defaults from `COLORFGBG` and can be
+/// overridden by the user (`set-option global giallo_background dark`) to
+/// flip both halves live without restarting the server.
+fn build_theme_variant_snippet(face_count: usize) -> String {
+    let mut snippet = String::new();
+
+    snippet.push_str("define-command -override giallo-apply-light-faces %{\n");
+    for i in 1..=face_count {
+        snippet.push_str(&format!(
+            "    set-face global giallo_{i:04} ref:giallo_light_{i:04}\n"
+        ));
+    }
+    snippet.push_str("}\n");
+
+    snippet.push_str("define-command -override giallo-apply-dark-faces %{\n");
+    for i in 1..=face_count {
+        snippet.push_str(&format!(
+            "    set-face global giallo_{i:04} ref:giallo_dark_{i:04}\n"
+        ));
+    }
+    snippet.push_str("}\n");
+
+    snippet.push_str("try %{ declare-option -hidden str giallo_background %sh{\n");
+    snippet.push_str("    case \"$COLORFGBG\" in\n");
+    snippet.push_str("        *\\;0|*\\;8) printf dark ;;\n");
+    snippet.push_str("        *) printf light ;;\n");
+    snippet.push_str("    esac\n");
+    snippet.push_str("} } catch %{}\n");
+
+    snippet.push_str("remove-hooks global giallo-theme\n");
+    snippet.push_str("hook -group giallo-theme global GlobalSetOption giallo_background=.* %{\n");
+    snippet.push_str(
+        "    evaluate-commands %sh{ [ \"$kak_opt_giallo_background\" = dark ] && printf giallo-apply-dark-faces || printf giallo-apply-light-faces }\n",
+    );
+    snippet.push_str("}\n");
+
+    snippet.push_str(
+        "evaluate-commands %sh{ [ \"$kak_opt_giallo_background\" = dark ] && printf giallo-apply-dark-faces || printf giallo-apply-light-faces }\n",
+    );
+
+    snippet
+}
+
+/// Convert an HSL color (hue in `0..360`, saturation/lightness in `0..100`)
+/// to 8-bit RGB, following the standard piecewise formula.
+fn hsl_to_rgb(h: f64, s: f64, l: f64) -> (u8, u8, u8) {
+    let s = s / 100.0;
+    let l = l / 100.0;
+    let c = (1.0 - (2.0 * l - 1.0).abs()) * s;
+    let h_prime = h / 60.0;
+    let x = c * (1.0 - (h_prime.rem_euclid(2.0) - 1.0).abs());
+    let (r1, g1, b1) = match h_prime as i64 {
+        0 => (c, x, 0.0),
+        1 => (x, c, 0.0),
+        2 => (0.0, c, x),
+        3 => (0.0, x, c),
+        4 => (x, 0.0, c),
+        _ => (c, 0.0, x),
+    };
+    let m = l - c / 2.0;
+    let to_u8 = |v: f64| ((v + m) * 255.0).round().clamp(0.0, 255.0) as u8;
+    (to_u8(r1), to_u8(g1), to_u8(b1))
+}
+
+/// rust-analyzer's `rainbowify` seeds a hue from a hash of the identifier;
+/// we don't have per-identifier hashes available (no capture-level text
+/// classification reaches this layer — see `rainbow_delimiter_output`), but
+/// we want the same property for bracket depth: deterministic, well spread
+/// colors that don't cluster. The golden angle (~137.5deg) does that with no
+/// PRNG state at all — each successive depth lands far from the last on the
+/// hue wheel, and the sequence is identical across runs for the same depth.
+fn rainbow_color_for_depth(depth: usize, color_count: usize) -> (u8, u8, u8) {
+    const GOLDEN_ANGLE: f64 = 137.50776;
+    let bucket = depth % color_count.max(1);
+    let hue = (bucket as f64 * GOLDEN_ANGLE).rem_euclid(360.0);
+    // Saturation/lightness fixed mid-range so every hue stays legible against
+    // both light and dark theme backgrounds.
+    hsl_to_rgb(hue, 70.0, 60.0)
+}
+
+/// Scan `highlighted`'s already-resolved tokens for `()`/`[]`/`{}` delimiter
+/// characters and tag each one with a `giallo_rainbow_<depth mod N>` face
+/// keyed by its nesting depth, all three bracket kinds sharing one depth
+/// counter (matching common editor rainbow-bracket behavior). This only
+/// needs the token text giallo already hands back, not which grammar scope
+/// produced it, so it works for any language without per-grammar query
+/// changes. Returns the `giallo_rainbow_*` face definitions and a
+/// space-joined ranges string in the same format `build_kakoune_commands`
+/// produces, ready to be appended to its output.
+fn rainbow_delimiter_output(highlighted: &giallo::HighlightedCode<'_>, color_count: usize) -> (Vec<FaceDef>, String) {
+    let faces = (0..color_count)
+        .map(|i| {
+            let (r, g, b) = rainbow_color_for_depth(i, color_count);
+            FaceDef {
+                name: format!("giallo_rainbow_{i:02}"),
+                spec: format!("rgb:{r:02x}{g:02x}{b:02x},default"),
+            }
+        })
+        .collect();
+
+    let mut ranges: Vec<String> = Vec::new();
+    let mut depth: usize = 0;
+
+    for (line_idx, line_tokens) in highlighted.tokens.iter().enumerate() {
+        let line = line_idx + 1;
+        let mut col = 0usize;
+        for token in line_tokens {
+            for ch in token.text.chars() {
+                let char_len = ch.len_utf8();
+                let is_open = matches!(ch, '(' | '[' | '{');
+                let is_close = matches!(ch, ')' | ']' | '}');
+
+                if is_open {
+                    let face = format!("giallo_rainbow_{:02}", depth % color_count.max(1));
+                    ranges.push(format!("{line}.{},{line}.{}|{face}", col + 1, col + char_len));
+                    depth += 1;
+                } else if is_close {
+                    depth = depth.saturating_sub(1);
+                    let face = format!("giallo_rainbow_{:02}", depth % color_count.max(1));
+                    ranges.push(format!("{line}.{},{line}.{}|{face}", col + 1, col + char_len));
+                }
+
+                col += char_len;
+            }
+        }
+    }
+
+    (faces, ranges.join(" "))
+}
+
+/// If rainbow delimiter highlighting is enabled, compute it and merge its
+/// faces/ranges into `faces`/`ranges` (Kakoune ranges are last-write-wins per
+/// byte span, so appending the rainbow ranges after the theme's own makes
+/// them take priority over the scope face at the same position).
+fn apply_rainbow(config: &Config, highlighted: &giallo::HighlightedCode<'_>, faces: &mut Vec<FaceDef>, ranges: &mut String) {
+    if !config.rainbow.enabled {
+        return;
+    }
+    let (rainbow_faces, rainbow_ranges) = rainbow_delimiter_output(highlighted, config.rainbow_color_count());
+    faces.extend(rainbow_faces);
+    if rainbow_ranges.is_empty() {
+        return;
+    }
+    if !ranges.is_empty() {
+        ranges.push(' ');
+    }
+    ranges.push_str(&rainbow_ranges);
+}
+
+/// Split `line` into `(word, byte_range)` pairs of maximal runs of
+/// identifier characters (`[A-Za-z0-9_]`), the only granularity
+/// `scan_semantic_modifiers` needs to match its keyword sequences. Bytes
+/// outside those runs (whitespace, punctuation, and any multi-byte UTF-8
+/// continuation bytes, which are never mistaken for ASCII identifier bytes)
+/// are simply skipped.
+fn word_spans(line: &str) -> Vec<(&str, std::ops::Range<usize>)> {
+    let mut spans = Vec::new();
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        let is_word_byte = |b: u8| b.is_ascii_alphanumeric() || b == b'_';
+        if is_word_byte(bytes[i]) {
+            let start = i;
+            while i < bytes.len() && is_word_byte(bytes[i]) {
+                i += 1;
+            }
+            spans.push((&line[start..i], start..i));
+        } else {
+            i += 1;
+        }
+    }
+    spans
+}
+
+/// Scan one line of reconstructed source text for the keyword sequences
+/// `SemanticConfig` recognizes, returning each match's face name and byte
+/// range within `line`: `unsafe` tags itself; `let mut NAME` and `fn NAME`
+/// tag the bound/declared identifier that follows. A hand-written word scan
+/// rather than a regex dependency, matching the rest of this codebase,
+/// which has none.
+fn scan_semantic_modifiers(line: &str) -> Vec<(&'static str, std::ops::Range<usize>)> {
+    let words = word_spans(line);
+    let mut matches = Vec::new();
+
+    for i in 0..words.len() {
+        let (word, range) = &words[i];
+        match *word {
+            "unsafe" => matches.push(("giallo_keyword_unsafe", range.clone())),
+            "let" if words.get(i + 1).is_some_and(|(w, _)| *w == "mut") => {
+                if let Some((_, ident_range)) = words.get(i + 2) {
+                    matches.push(("giallo_variable_mutable", ident_range.clone()));
+                }
+            }
+            "fn" => {
+                if let Some((_, ident_range)) = words.get(i + 1) {
+                    matches.push(("giallo_function_declaration", ident_range.clone()));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    matches
+}
+
+/// Reconstruct each line's source text from `highlighted`'s tokens and run
+/// `scan_semantic_modifiers` over it, producing the `giallo_*` face
+/// definitions and a space-joined ranges string in the same format
+/// `build_kakoune_commands` produces, ready to be appended to its output.
+fn semantic_modifier_output(highlighted: &giallo::HighlightedCode<'_>) -> (Vec<FaceDef>, String) {
+    let faces = vec![
+        FaceDef {
+            name: "giallo_variable_mutable".to_string(),
+            spec: "ref:variable".to_string(),
+        },
+        FaceDef {
+            name: "giallo_function_declaration".to_string(),
+            spec: "ref:function".to_string(),
+        },
+        FaceDef {
+            name: "giallo_keyword_unsafe".to_string(),
+            spec: "ref:keyword".to_string(),
+        },
+    ];
+
+    let mut ranges: Vec<String> = Vec::new();
+
+    for (line_idx, line_tokens) in highlighted.tokens.iter().enumerate() {
+        let line = line_idx + 1;
+        let line_text: String = line_tokens.iter().map(|t| t.text).collect();
+
+        for (face, byte_range) in scan_semantic_modifiers(&line_text) {
+            ranges.push(format!("{line}.{},{line}.{}|{face}", byte_range.start + 1, byte_range.end));
+        }
+    }
+
+    (faces, ranges.join(" "))
+}
+
+/// If semantic modifier highlighting is enabled, compute it and merge its
+/// faces/ranges into `faces`/`ranges`, appended last so they take priority
+/// over both the theme's own ranges and rainbow delimiter ranges at the same
+/// byte span (same last-write-wins reasoning as `apply_rainbow`).
+fn apply_semantic_modifiers(
+    config: &Config,
+    highlighted: &giallo::HighlightedCode<'_>,
+    faces: &mut Vec<FaceDef>,
+    ranges: &mut String,
+) {
+    if !config.semantic.enabled {
+        return;
+    }
+    let (semantic_faces, semantic_ranges) = semantic_modifier_output(highlighted);
+    faces.extend(semantic_faces);
+    if semantic_ranges.is_empty() {
+        return;
+    }
+    if !ranges.is_empty() {
+        ranges.push(' ');
+    }
+    ranges.push_str(&semantic_ranges);
+}
+
+fn html_escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+/// Render one style as inline CSS declarations, shared by both the `css`
+/// and `html` oneshot output modes.
+fn css_style_rule(style: &giallo::Style) -> String {
+    let mut decls = vec![
+        format!("color: #{}", strip_hash(&normalize_hex(&style.foreground.as_hex()))),
+        format!(
+            "background-color: #{}",
+            strip_hash(&normalize_hex(&style.background.as_hex()))
+        ),
+    ];
+    if style.font_style.contains(giallo::FontStyle::BOLD) {
+        decls.push("font-weight: bold".to_string());
+    }
+    if style.font_style.contains(giallo::FontStyle::ITALIC) {
+        decls.push("font-style: italic".to_string());
+    }
+    let mut text_decoration = Vec::new();
+    if style.font_style.contains(giallo::FontStyle::UNDERLINE) {
+        text_decoration.push("underline");
+    }
+    if style.font_style.contains(giallo::FontStyle::STRIKETHROUGH) {
+        text_decoration.push("line-through");
+    }
+    if !text_decoration.is_empty() {
+        decls.push(format!("text-decoration: {}", text_decoration.join(" ")));
+    }
+    decls.join("; ")
+}
+
+/// `Dual` themes have no single "the" background for a static export, so
+/// HTML/CSS output always renders against the light half of the pair.
+fn output_theme(highlighted: &giallo::HighlightedCode<'_>) -> &giallo::Theme {
+    match highlighted.theme {
+        ThemeVariant::Single(theme) => theme,
+        ThemeVariant::Dual { light, .. } => light,
+    }
+}
+
+/// Render a CSS stylesheet of one class per distinct style plus `<pre><span
+/// class="...">` markup referencing those classes — stable class names per
+/// scope so one stylesheet can serve many exported snippets.
+fn build_css_output(highlighted: &giallo::HighlightedCode<'_>) -> String {
+    let default_style = output_theme(highlighted).default_style;
+
+    let mut class_map: HashMap<StyleKey, String> = HashMap::new();
+    let mut stylesheet = String::new();
+    let mut body = String::from("<pre>\n");
+
+    for line_tokens in highlighted.tokens.iter() {
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+            let style = match token.style {
+                ThemeVariant::Single(style) => style,
+                ThemeVariant::Dual { light, .. } => light,
+            };
+            let escaped = html_escape(token.text);
+            if style == default_style {
+                body.push_str(&escaped);
+                continue;
+            }
+
+            let key = style_key(&style);
+            let class_name = match class_map.get(&key) {
+                Some(name) => name.clone(),
+                None => {
+                    let name = format!("giallo-{:04}", class_map.len() + 1);
+                    stylesheet.push_str(&format!(".{} {{ {} }}\n", name, css_style_rule(&style)));
+                    class_map.insert(key, name.clone());
+                    name
+                }
+            };
+            body.push_str(&format!("<span class=\"{class_name}\">{escaped}</span>"));
+        }
+        body.push('\n');
+    }
+    body.push_str("</pre>\n");
+
+    format!("<style>\n{stylesheet}</style>\n{body}")
+}
+
+/// Render self-contained HTML with inline `style="..."` spans — no external
+/// stylesheet required, at the cost of repeating each style's declarations.
+fn build_html_output(highlighted: &giallo::HighlightedCode<'_>) -> String {
+    let default_style = output_theme(highlighted).default_style;
+
+    let mut body = String::from("<pre>\n");
+    for line_tokens in highlighted.tokens.iter() {
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+            let style = match token.style {
+                ThemeVariant::Single(style) => style,
+                ThemeVariant::Dual { light, .. } => light,
+            };
+            let escaped = html_escape(token.text);
+            if style == default_style {
+                body.push_str(&escaped);
+            } else {
+                body.push_str(&format!(
+                    "<span style=\"{}\">{escaped}</span>",
+                    css_style_rule(&style)
+                ));
+            }
+        }
+        body.push('\n');
+    }
+    body.push_str("</pre>\n");
+    body
+}
+
+/// Per-line git diff status used to drive the gutter faces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum DiffLineKind {
+    Added,
+    Modified,
+    Removed,
+}
+
+impl DiffLineKind {
+    fn face(self) -> &'static str {
+        match self {
+            DiffLineKind::Added => "giallo_diff_added",
+            DiffLineKind::Modified => "giallo_diff_modified",
+            DiffLineKind::Removed => "giallo_diff_removed",
+        }
+    }
+}
+
+/// Diff `file_path` against HEAD and return the working-tree lines that were
+/// added or changed, plus the lines immediately preceding a pure deletion.
+fn compute_diff_lines(file_path: &Path) -> Option<Vec<(usize, DiffLineKind)>> {
+    let repo_dir = file_path.parent().unwrap_or_else(|| Path::new("."));
+    let repo = git2::Repository::discover(repo_dir).ok()?;
+    let workdir = repo.workdir()?;
+    let rel_path = file_path.strip_prefix(workdir).ok()?;
+
+    let mut opts = git2::DiffOptions::new();
+    opts.pathspec(rel_path);
+
+    let head = repo.head().ok()?.peel_to_tree().ok();
+    let diff = repo
+        .diff_tree_to_workdir_with_index(head.as_ref(), Some(&mut opts))
+        .ok()?;
+
+    let mut lines: Vec<(usize, DiffLineKind)> = Vec::new();
+    let _ = diff.foreach(
+        &mut |_delta, _progress| true,
+        None,
+        Some(&mut |_delta, hunk| {
+            let start = hunk.new_start() as usize;
+            let added = hunk.new_lines() as usize;
+            let removed_only = added == 0 && hunk.old_lines() > 0;
+            if removed_only {
+                lines.push((start.max(1), DiffLineKind::Removed));
+            } else {
+                let kind = if hunk.old_lines() == 0 {
+                    DiffLineKind::Added
+                } else {
+                    DiffLineKind::Modified
+                };
+                for line in start..start + added {
+                    lines.push((line, kind));
+                }
+            }
+            true
+        }),
+        None,
+    );
+
+    Some(lines)
+}
+
+/// Build the `set-face`/`set-option buffer giallo_diff_ranges` commands for a
+/// buffer's file, or `None` if it isn't tracked in a git working tree.
+fn build_diff_commands(file_path: &Path) -> Option<String> {
+    let lines = compute_diff_lines(file_path)?;
+
+    let mut commands = String::new();
+    commands.push_str("set-face global giallo_diff_added green\n");
+    commands.push_str("set-face global giallo_diff_modified yellow\n");
+    commands.push_str("set-face global giallo_diff_removed red\n");
+
+    let ranges: Vec<String> = lines
+        .iter()
+        .map(|(line, kind)| format!("{line}.1,{line}.1|{}", kind.face()))
+        .collect();
+
+    commands.push_str("set-option buffer giallo_diff_ranges %val{timestamp}");
+    if !ranges.is_empty() {
+        commands.push(' ');
+        commands.push_str(&ranges.join(" "));
+    }
+    commands.push('\n');
+
+    Some(commands)
+}
+
 fn build_commands(faces: &[FaceDef], ranges: &str) -> String {
     let mut commands = String::new();
     for face in faces {
@@ -206,7 +849,6 @@ fn build_commands(faces: &[FaceDef], ranges: &str) -> String {
     commands
 }
 
-#[allow(dead_code)]
 fn write_response(mut out: impl Write, commands: &str) -> io::Result<()> {
     let len = commands.as_bytes().len();
     writeln!(out, "OK {len}")?;
@@ -214,7 +856,6 @@ fn write_response(mut out: impl Write, commands: &str) -> io::Result<()> {
     out.flush()
 }
 
-#[allow(dead_code)]
 fn read_exact_bytes(reader: &mut impl Read, len: usize) -> io::Result<Vec<u8>> {
     let mut buf = vec![0u8; len];
     reader.read_exact(&mut buf)?;
@@ -225,16 +866,56 @@ fn kak_quote(input: &str) -> String {
     input.replace('\'', "''")
 }
 
-fn send_to_kak(session: &str, buffer: &str, payload: &str) -> io::Result<()> {
-    let mut cmd = String::new();
-    cmd.push_str("evaluate-commands -no-hooks -buffer '");
-    cmd.push_str(&kak_quote(buffer));
-    cmd.push_str("' -- %[ ");
-    cmd.push_str(payload);
-    cmd.push_str(" ]\n");
+/// Path to `session`'s Kakoune command FIFO — the same file `kak -p
+/// <session>` forwards its stdin onto — or `None` if it can't be resolved,
+/// in which case `send_to_kak` falls back to spawning `kak -p`.
+fn kak_command_fifo_path(session: &str) -> Option<PathBuf> {
+    let runtime_dir = std::env::var_os("XDG_RUNTIME_DIR")?;
+    let path = PathBuf::from(runtime_dir).join("kakoune").join(session);
+    path.exists().then_some(path)
+}
 
-    log::trace!(
-        "send_to_kak: sending {} bytes to kak -p {}",
+/// Write `cmd` straight to `session`'s command FIFO, opening (and caching
+/// in `kak_pipe`) the connection on first use so later calls reuse it
+/// instead of spawning a new `kak -p` process. Returns `Err` if the FIFO
+/// can't be resolved, opened, or written to — the caller falls back to
+/// `kak -p` in that case, and a write failure drops the cached handle so
+/// the next call reopens from scratch rather than repeating a stale error.
+fn send_to_kak_pipe(session: &str, kak_pipe: &Mutex<Option<fs::File>>, cmd: &str) -> io::Result<()> {
+    let mut pipe = kak_pipe.lock().unwrap();
+    if pipe.is_none() {
+        let path = kak_command_fifo_path(session).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "kakoune command fifo not found")
+        })?;
+        *pipe = Some(OpenOptions::new().write(true).open(&path)?);
+        log::debug!("send_to_kak: opened persistent command fifo at {:?}", path);
+    }
+
+    let file = pipe.as_mut().expect("populated above");
+    match file.write_all(cmd.as_bytes()) {
+        Ok(()) => Ok(()),
+        Err(err) => {
+            *pipe = None;
+            Err(err)
+        }
+    }
+}
+
+fn send_to_kak(
+    session: &str,
+    buffer: &str,
+    kak_pipe: &Mutex<Option<fs::File>>,
+    payload: &str,
+) -> io::Result<()> {
+    let mut cmd = String::new();
+    cmd.push_str("evaluate-commands -no-hooks -buffer '");
+    cmd.push_str(&kak_quote(buffer));
+    cmd.push_str("' -- %[ ");
+    cmd.push_str(payload);
+    cmd.push_str(" ]\n");
+
+    log::trace!(
+        "send_to_kak: sending {} bytes to session {}",
         cmd.len(),
         session
     );
@@ -257,6 +938,16 @@ fn send_to_kak(session: &str, buffer: &str, payload: &str) -> io::Result<()> {
         }
     }
 
+    match send_to_kak_pipe(session, kak_pipe, &cmd) {
+        Ok(()) => return Ok(()),
+        Err(err) => {
+            log::debug!(
+                "send_to_kak: persistent fifo unavailable ({}), falling back to `kak -p`",
+                err
+            );
+        }
+    }
+
     // Check if kak is available
     if which::which("kak").is_err() {
         log::error!("send_to_kak: kak command not found in PATH");
@@ -289,21 +980,71 @@ fn highlight_and_send(
     registry: &Registry,
     config: &Config,
     ctx: &BufferContext,
+    viewport: Option<(usize, usize)>,
+    generation: u64,
 ) {
     let resolved_lang = config.resolve_lang(lang);
     let resolved_theme = config.resolve_theme(theme);
+    let hash = content_hash(text);
+
+    *ctx.last_text.lock().unwrap() = Some(text.to_string());
+
+    // A newer buffer snapshot has already been queued (or a `SET_LANG`/
+    // `SET_THEME` has preempted us) — this pass is already stale, so don't
+    // bother parsing/highlighting at all.
+    if ctx.generation.load(Ordering::Relaxed) != generation {
+        log::debug!(
+            "highlight: buffer={} generation {} superseded before starting, skipping",
+            ctx.buffer,
+            generation
+        );
+        return;
+    }
 
     log::debug!(
-        "highlight: buffer={} lang={} (resolved={}) theme={} (resolved={}) text_len={}",
+        "highlight: buffer={} lang={} (resolved={}) theme={} (resolved={}) text_len={} viewport={:?}",
         ctx.buffer,
         lang,
         resolved_lang,
         theme,
         resolved_theme,
-        text.len()
+        text.len(),
+        viewport
     );
 
-    let options = HighlightOptions::new(&resolved_lang, ThemeVariant::Single(resolved_theme));
+    // Fast path: content hasn't changed since the last full highlight and
+    // Kakoune only wants the visible window refreshed (e.g. on scroll) — skip
+    // re-parsing/re-highlighting and resend a filtered slice of the cached
+    // ranges instead.
+    if let Some((first, last)) = viewport {
+        let cached = ctx.highlight_cache.lock().unwrap().clone();
+        if let Some(cache) = cached {
+            if cache.content_hash == hash {
+                if ctx.generation.load(Ordering::Relaxed) != generation {
+                    log::debug!(
+                        "highlight: buffer={} generation {} superseded, discarding viewport resend",
+                        ctx.buffer,
+                        generation
+                    );
+                    return;
+                }
+                let windowed = filter_ranges_to_window(&cache.ranges, first, last);
+                let commands = build_commands(&[], &windowed);
+                if let Err(err) = send_to_kak(&ctx.session, &ctx.buffer, &ctx.kak_pipe, &commands) {
+                    log::error!("highlight: failed to resend viewport ranges to kak: {}", err);
+                } else {
+                    log::debug!(
+                        "highlight: resent cached ranges for viewport {}..{} (no re-highlight)",
+                        first,
+                        last
+                    );
+                }
+                return;
+            }
+        }
+    }
+
+    let options = HighlightOptions::new(&resolved_lang, parse_theme_spec(resolved_theme));
     let highlighted = match registry.highlight(text, &options) {
         Ok(h) => {
             log::debug!("highlight: success for {} tokens", h.tokens.len());
@@ -322,7 +1063,7 @@ fn highlight_and_send(
                 err
             );
             let fallback =
-                HighlightOptions::new(PLAIN_GRAMMAR_NAME, ThemeVariant::Single(resolved_theme));
+                HighlightOptions::new(PLAIN_GRAMMAR_NAME, parse_theme_spec(resolved_theme));
             match registry.highlight(text, &fallback) {
                 Ok(h) => {
                     log::debug!("highlight: fallback success for {} tokens", h.tokens.len());
@@ -337,7 +1078,9 @@ fn highlight_and_send(
         }
     };
 
-    let (faces, ranges) = build_kakoune_commands(&highlighted);
+    let (mut faces, mut ranges, theme_variant_snippet) = build_kakoune_commands(&highlighted, config);
+    apply_rainbow(config, &highlighted, &mut faces, &mut ranges);
+    apply_semantic_modifiers(config, &highlighted, &mut faces, &mut ranges);
     log::debug!(
         "highlight: built {} faces and {} ranges",
         faces.len(),
@@ -348,23 +1091,57 @@ fn highlight_and_send(
         }
     );
 
-    let commands = build_commands(&faces, &ranges);
+    *ctx.highlight_cache.lock().unwrap() = Some(BufferHighlightCache {
+        content_hash: hash,
+        ranges: ranges.clone(),
+    });
+
+    let sent_ranges = match viewport {
+        Some((first, last)) => filter_ranges_to_window(&ranges, first, last),
+        None => ranges,
+    };
+
+    let mut commands = build_commands(&faces, &sent_ranges);
+    if let Some(snippet) = theme_variant_snippet {
+        commands.push_str(&snippet);
+    }
     log::trace!("highlight: sending commands:\n{}", commands);
 
-    if let Err(err) = send_to_kak(&ctx.session, &ctx.buffer, &commands) {
+    if ctx.generation.load(Ordering::Relaxed) != generation {
+        log::debug!(
+            "highlight: buffer={} generation {} superseded after highlighting, discarding result",
+            ctx.buffer,
+            generation
+        );
+        return;
+    }
+
+    if let Err(err) = send_to_kak(&ctx.session, &ctx.buffer, &ctx.kak_pipe, &commands) {
         log::error!("highlight: failed to send to kak: {}", err);
         eprintln!("failed to send highlights to kak: {err}");
     } else {
         log::debug!("highlight: sent highlights to kak successfully");
     }
+
+    if let Some(ref file_path) = ctx.file_path {
+        match build_diff_commands(file_path) {
+            Some(diff_commands) => {
+                if let Err(err) = send_to_kak(&ctx.session, &ctx.buffer, &ctx.kak_pipe, &diff_commands) {
+                    log::warn!("highlight: failed to send diff gutter to kak: {}", err);
+                }
+            }
+            None => log::debug!("highlight: no git diff available for {:?}", file_path),
+        }
+    }
 }
 
 fn run_buffer_fifo(
     req_path: &Path,
-    registry: &Registry,
+    registry: &Arc<Mutex<Registry>>,
     config: &Config,
     ctx: BufferContext,
     quit_flag: Option<&Arc<AtomicBool>>,
+    worker: Option<&WorkerHandles>,
 ) -> io::Result<()> {
     log::debug!(
         "buffer FIFO: starting for buffer={} sentinel={}",
@@ -378,6 +1155,7 @@ fn run_buffer_fifo(
     // Clone context and quit flag for the reader thread
     let ctx_clone = ctx.clone();
     let quit_flag_clone = quit_flag.map(|f| f.clone());
+    let worker_clone = worker.cloned();
     let req_path_owned = req_path.to_path_buf();
 
     // Spawn reader thread - continuously reads from FIFO
@@ -395,12 +1173,17 @@ fn run_buffer_fifo(
         };
 
         loop {
-            // Check quit signal
+            // Check quit signal (global shutdown or this buffer's own KILL)
             if let Some(ref flag) = quit_flag_clone {
                 if flag.load(Ordering::Relaxed) {
                     break;
                 }
             }
+            if let Some(ref worker) = worker_clone {
+                if worker.is_cancelled() {
+                    break;
+                }
+            }
 
             // Try to read data from FIFO
             let mut read_buf = String::new();
@@ -431,6 +1214,12 @@ fn run_buffer_fifo(
                 let end_index = index + sentinel.len();
                 buf.drain(..end_index);
 
+                // Bump the generation before handing off so a highlight
+                // pass already in flight for an older snapshot notices it's
+                // been superseded and discards its result instead of
+                // clobbering this newer one.
+                ctx_clone.generation.fetch_add(1, Ordering::Relaxed);
+
                 // Send complete message to processing thread
                 if tx.send(content).is_err() {
                     log::debug!("reader: channel closed, exiting");
@@ -441,33 +1230,90 @@ fn run_buffer_fifo(
     });
 
     // Processing loop - receives messages and processes highlights
+    let mut last_dispatch: Option<std::time::Instant> = None;
     loop {
-        // Check quit signal
-        if let Some(flag) = quit_flag {
-            if flag.load(Ordering::Relaxed) {
-                // Drop the receiver to close the channel, which will cause
-                // the reader thread to get an error on send and exit
-                drop(rx);
-                let _ = reader_handle.join();
-                break;
+        // Check quit signal (global shutdown or this buffer's own KILL)
+        let cancelled = quit_flag.is_some_and(|f| f.load(Ordering::Relaxed))
+            || worker.is_some_and(|w| w.is_cancelled());
+        if cancelled {
+            // Drop the receiver to close the channel, which will cause
+            // the reader thread to get an error on send and exit
+            drop(rx);
+            let _ = reader_handle.join();
+            if let Some(worker) = worker {
+                worker.mark_killed();
             }
+            break;
         }
 
         // Try to receive a message with timeout
         match rx.recv_timeout(std::time::Duration::from_millis(100)) {
-            Ok(content) => {
+            Ok(mut content) => {
+                // Debounce: a burst of keystrokes queues one message per
+                // edit, but only the most recent one is worth highlighting.
+                // Give the FIFO a short window to deliver any follow-up
+                // edits and keep only the latest, collapsing the whole
+                // burst into a single highlight pass.
+                let debounce = config.debounce();
+                if !debounce.is_zero() {
+                    thread::sleep(debounce);
+                    while let Ok(newer) = rx.try_recv() {
+                        content = newer;
+                    }
+                }
+
+                // Beyond burst coalescing, also enforce a minimum spacing
+                // between dispatches for steady, evenly-paced typing where
+                // each edit lands just outside the debounce window — wait
+                // out the remainder of the interval (picking up any newer
+                // content that arrives while doing so) before highlighting.
+                let min_interval = config.min_highlight_interval();
+                if !min_interval.is_zero() {
+                    if let Some(last) = last_dispatch {
+                        let elapsed = last.elapsed();
+                        if elapsed < min_interval {
+                            thread::sleep(min_interval - elapsed);
+                            while let Ok(newer) = rx.try_recv() {
+                                content = newer;
+                            }
+                        }
+                    }
+                }
+
+                let cancelled = quit_flag.is_some_and(|f| f.load(Ordering::Relaxed))
+                    || worker.is_some_and(|w| w.is_cancelled());
+                if cancelled {
+                    continue;
+                }
+
+                last_dispatch = Some(std::time::Instant::now());
+
                 let lang = ctx.lang.lock().unwrap().clone();
                 let theme = ctx.theme.lock().unwrap().clone();
+                let (viewport, text) = parse_viewport_header(&content);
+                let generation = ctx.generation.load(Ordering::Relaxed);
 
                 log::debug!(
-                    "processor: received buffer (lang={} theme={} len={})",
+                    "processor: received buffer (lang={} theme={} len={} viewport={:?})",
                     lang,
                     theme,
-                    content.len()
+                    text.len(),
+                    viewport
                 );
 
                 if !lang.is_empty() {
-                    highlight_and_send(&content, &lang, &theme, registry, config, &ctx);
+                    if let Some(worker) = worker {
+                        worker.mark_highlighting();
+                    }
+                    {
+                        let reg = registry.lock().unwrap();
+                        highlight_and_send(
+                            text, &lang, &theme, &reg, config, &ctx, viewport, generation,
+                        );
+                    }
+                    if let Some(worker) = worker {
+                        worker.mark_idle();
+                    }
                 } else {
                     log::warn!(
                         "processor: empty language, skipping highlight for buffer={}",
@@ -486,6 +1332,103 @@ fn run_buffer_fifo(
     Ok(())
 }
 
+/// Walk `dir`, pre-highlighting recognized source files into
+/// `resources.crawl_cache` so a later INIT/H for one of them is immediate.
+/// Stops as soon as the cache's file-count cap is reached; the cache itself
+/// also enforces the memory budget via LRU eviction (see `CrawlCache`).
+fn crawl_directory(
+    dir: &Path,
+    registry: &Arc<Mutex<Registry>>,
+    config: &Config,
+    resources: &ServerResources,
+) -> usize {
+    let max_files = config.crawl.max_files.unwrap_or(DEFAULT_CRAWL_MAX_FILES);
+    let theme = config.resolve_theme("").to_string();
+    let mut cached = 0usize;
+    let mut dirs = vec![dir.to_path_buf()];
+
+    while let Some(current) = dirs.pop() {
+        if resources.crawl_cache.lock().unwrap().len() >= max_files {
+            log::info!("CRAWL: reached max_files={} cap, stopping", max_files);
+            break;
+        }
+
+        let entries = match fs::read_dir(&current) {
+            Ok(entries) => entries,
+            Err(err) => {
+                log::warn!("CRAWL: failed to read dir {}: {}", current.display(), err);
+                continue;
+            }
+        };
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if entry.file_name().to_string_lossy().starts_with('.') {
+                continue;
+            }
+
+            if path.is_dir() {
+                dirs.push(path);
+                continue;
+            }
+
+            if resources.crawl_cache.lock().unwrap().len() >= max_files {
+                break;
+            }
+
+            let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+                continue;
+            };
+
+            let resolved_lang = config.resolve_lang(ext);
+            let reg = registry.lock().unwrap();
+            let known = reg.contains_grammar(&resolved_lang);
+            if !known && !config.crawl.all_files.unwrap_or(false) {
+                continue;
+            }
+            let lang = if known {
+                resolved_lang.clone()
+            } else {
+                PLAIN_GRAMMAR_NAME.to_string()
+            };
+
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+
+            let options = HighlightOptions::new(&lang, parse_theme_spec(&theme));
+            let highlighted = match reg.highlight(&text, &options) {
+                Ok(h) => h,
+                Err(err) => {
+                    log::debug!("CRAWL: highlight failed for {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+
+            let (mut faces, mut ranges, theme_variant_snippet) = build_kakoune_commands(&highlighted, config);
+            apply_rainbow(config, &highlighted, &mut faces, &mut ranges);
+            apply_semantic_modifiers(config, &highlighted, &mut faces, &mut ranges);
+            let mut commands = build_commands(&faces, &ranges);
+            if let Some(snippet) = theme_variant_snippet {
+                commands.push_str(&snippet);
+            }
+            drop(reg);
+
+            resources.crawl_cache.lock().unwrap().insert(
+                path.clone(),
+                CrawlEntry {
+                    lang,
+                    theme: theme.clone(),
+                    commands,
+                },
+            );
+            cached += 1;
+        }
+    }
+
+    cached
+}
+
 enum Mode {
     Stdio,
     Oneshoot,
@@ -493,9 +1436,63 @@ enum Mode {
     KakouneRc,
     ListGrammars,
     ListThemes,
+    LintTheme { theme: String, lang: String, sample_path: Option<String> },
+    ShowThemes { lang: String, sample_path: Option<String>, theme_name: Option<String> },
+    Install { manifest: String },
+    FetchGrammars { force: bool },
 }
 
-fn parse_args() -> (Mode, bool) {
+/// Why argument parsing didn't produce a `(Mode, bool)` to run with —
+/// either a real error, or one of `--help`/`--version`'s "print this and
+/// exit 0" early exits. Keeping these as data (rather than calling
+/// `process::exit` inline) keeps `parse_args_from` pure and testable, and
+/// leaves the actual exiting/printing to `main`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum ArgError {
+    /// `--help`/`-h` was passed; caller should print this text and exit 0.
+    Help(String),
+    /// `--version` was passed; caller should print this text and exit 0.
+    Version(String),
+    /// An argument wasn't recognized at all.
+    UnknownFlag(String),
+    /// A flag that takes a value (e.g. `--fifo`) had none following it.
+    MissingValue(&'static str),
+}
+
+const HELP_TEXT: &str = "\
+giallo-kak - tree-sitter highlighting server for Kakoune
+
+USAGE:
+    giallo-kak [OPTIONS] [COMMAND]
+
+COMMANDS:
+    init                   Print the Kakoune rc snippet
+    list-grammars          List available grammars
+    list-themes            List available themes
+    show-themes            Preview every theme (or --theme <name>) against a sample
+    install <manifest>     Fetch GitHub-hosted grammar/theme assets from a manifest
+    fetch-grammars         Fetch every source pinned in grammars.toml
+
+OPTIONS:
+    --oneshot              Highlight a single buffer then exit
+    --fifo <path>          Read requests from a FIFO
+    --resp <path>          Write responses to a FIFO (defaults to stdout)
+    --lint-theme <theme>   Report theme scopes with no non-default style
+    --lang <lang>          Language for --lint-theme/show-themes
+    --file <path>          Sample file for --lint-theme/show-themes
+    --theme <name>         Preview a single theme with show-themes
+    --force                Re-fetch everything with fetch-grammars
+    --verbose, -v          Enable verbose logging
+    --help, -h             Print this help and exit
+    --version              Print the version and exit
+";
+
+/// Parse `args` (excluding the program name) into a `(Mode, bool)` of
+/// `(mode, verbose)`, or an `ArgError` describing why it couldn't. Pure and
+/// independent of `std::env::args`/process exit so it can be unit-tested
+/// and called from a library context; see `parse_args` for the real entry
+/// point.
+fn parse_args_from<I: IntoIterator<Item = String>>(args: I) -> Result<(Mode, bool), ArgError> {
     let mut oneshot = false;
     let mut fifo_req: Option<String> = None;
     let mut fifo_resp: Option<String> = None;
@@ -503,35 +1500,77 @@ fn parse_args() -> (Mode, bool) {
     let mut verbose = false;
     let mut list_grammars = false;
     let mut list_themes = false;
-
-    let mut args = std::env::args().skip(1);
+    let mut show_themes = false;
+    let mut lint_theme: Option<String> = None;
+    let mut lint_lang = "rust".to_string();
+    let mut lint_sample: Option<String> = None;
+    let mut show_theme_name: Option<String> = None;
+    let mut install_manifest: Option<String> = None;
+    let mut fetch_grammars = false;
+    let mut force = false;
+
+    let mut args = args.into_iter();
     while let Some(arg) = args.next() {
         match arg.as_str() {
+            "--help" | "-h" => return Err(ArgError::Help(HELP_TEXT.to_string())),
             "--version" => {
                 let commit = option_env!("GIT_COMMIT").unwrap_or("unknown");
-                println!("giallo-kak {} ({})", env!("CARGO_PKG_VERSION"), commit);
-                process::exit(0);
+                return Err(ArgError::Version(format!(
+                    "giallo-kak {} ({})",
+                    env!("CARGO_PKG_VERSION"),
+                    commit
+                )));
             }
             "--verbose" | "-v" => verbose = true,
             "--oneshot" => oneshot = true,
             "init" | "--kakoune" | "--print-rc" => kakoune_rc = true,
             "list-grammars" | "--list-grammars" => list_grammars = true,
             "list-themes" | "--list-themes" => list_themes = true,
+            "show-themes" | "--show-themes" => show_themes = true,
+            "--lint-theme" => {
+                lint_theme = Some(args.next().ok_or(ArgError::MissingValue("--lint-theme"))?);
+            }
+            "--lang" => {
+                lint_lang = args.next().ok_or(ArgError::MissingValue("--lang"))?;
+            }
+            "--file" => {
+                lint_sample = Some(args.next().ok_or(ArgError::MissingValue("--file"))?);
+            }
+            "--theme" => {
+                show_theme_name = Some(args.next().ok_or(ArgError::MissingValue("--theme"))?);
+            }
             "--fifo" => {
-                if let Some(path) = args.next() {
-                    fifo_req = Some(path);
-                }
+                fifo_req = Some(args.next().ok_or(ArgError::MissingValue("--fifo"))?);
             }
             "--resp" => {
-                if let Some(path) = args.next() {
-                    fifo_resp = Some(path);
-                }
+                fifo_resp = Some(args.next().ok_or(ArgError::MissingValue("--resp"))?);
             }
-            _ => {}
+            "install" | "--install" => {
+                install_manifest = Some(args.next().ok_or(ArgError::MissingValue("install"))?);
+            }
+            "fetch-grammars" | "--fetch-grammars" => fetch_grammars = true,
+            "--force" => force = true,
+            other => return Err(ArgError::UnknownFlag(other.to_string())),
         }
     }
 
-    let mode = if list_grammars {
+    let mode = if let Some(manifest) = install_manifest {
+        Mode::Install { manifest }
+    } else if fetch_grammars {
+        Mode::FetchGrammars { force }
+    } else if let Some(theme) = lint_theme {
+        Mode::LintTheme {
+            theme,
+            lang: lint_lang,
+            sample_path: lint_sample,
+        }
+    } else if show_themes {
+        Mode::ShowThemes {
+            lang: lint_lang,
+            sample_path: lint_sample,
+            theme_name: show_theme_name,
+        }
+    } else if list_grammars {
         Mode::ListGrammars
     } else if list_themes {
         Mode::ListThemes
@@ -548,42 +1587,900 @@ fn parse_args() -> (Mode, bool) {
         Mode::Stdio
     };
 
-    (mode, verbose)
+    Ok((mode, verbose))
+}
+
+/// Parse the process's real command-line arguments (see `parse_args_from`
+/// for the testable, exit-free core).
+fn parse_args() -> Result<(Mode, bool), ArgError> {
+    parse_args_from(std::env::args().skip(1))
+}
+
+/// A small multi-construct sample used when the user doesn't supply `--file`.
+const LINT_THEME_SAMPLE: &str = r#"// sample used to probe theme scope coverage
+use std::collections::HashMap;
+
+/// A doc comment
+struct Example<T> {
+    field: T,
+}
+
+impl<T> Example<T> {
+    fn new(field: T) -> Self {
+        Self { field }
+    }
+}
+
+fn main() {
+    let mut map: HashMap<String, i32> = HashMap::new();
+    map.insert("key".to_string(), 42);
+    if map.len() > 0 {
+        println!("{}", map.len());
+    }
+}
+"#;
+
+/// Walk `highlighted.tokens`, grouping scopes by whether they ever resolved
+/// to anything other than the theme's `default_style`, and print the sorted
+/// list of scopes that never received a non-default style.
+fn lint_theme_report(highlighted: &giallo::HighlightedCode<'_>) {
+    let theme = match highlighted.theme {
+        ThemeVariant::Single(theme) => theme,
+        ThemeVariant::Dual { light, .. } => light,
+    };
+    let default_style = theme.default_style;
+
+    let mut covered: std::collections::HashSet<String> = std::collections::HashSet::new();
+    let mut uncovered: std::collections::HashSet<String> = std::collections::HashSet::new();
+
+    for line_tokens in highlighted.tokens.iter() {
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+            let ThemeVariant::Single(style) = token.style else {
+                continue;
+            };
+            let scope = token.scope.to_string();
+            if style == default_style {
+                uncovered.insert(scope);
+            } else {
+                covered.insert(scope);
+            }
+        }
+    }
+
+    let mut never_covered: Vec<&String> = uncovered.difference(&covered).collect();
+    never_covered.sort();
+
+    println!("Scopes with no non-default style ({}):", never_covered.len());
+    for scope in never_covered {
+        println!("  {}", scope);
+    }
+}
+
+fn run_lint_theme(registry: &Registry, config: &Config, theme: &str, lang: &str, sample_path: Option<&str>) {
+    let resolved_theme = config.resolve_theme(theme);
+    let resolved_lang = config.resolve_lang(lang);
+
+    let sample = match sample_path {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => LINT_THEME_SAMPLE.to_string(),
+    };
+
+    let options = HighlightOptions::new(&resolved_lang, ThemeVariant::Single(resolved_theme));
+    match registry.highlight(&sample, &options) {
+        Ok(highlighted) => lint_theme_report(&highlighted),
+        Err(err) => {
+            eprintln!("highlight error: {err}");
+            process::exit(1);
+        }
+    }
+}
+
+fn hex_to_rgb(hex: &str) -> (u8, u8, u8) {
+    let hex = strip_hash(hex);
+    let r = hex.get(0..2).and_then(|v| u8::from_str_radix(v, 16).ok()).unwrap_or(0);
+    let g = hex.get(2..4).and_then(|v| u8::from_str_radix(v, 16).ok()).unwrap_or(0);
+    let b = hex.get(4..6).and_then(|v| u8::from_str_radix(v, 16).ok()).unwrap_or(0);
+    (r, g, b)
+}
+
+/// Render one styled token as a 24-bit truecolor ANSI escape sequence.
+fn render_ansi_token(text: &str, style: &giallo::Style) -> String {
+    let (fr, fg, fb) = hex_to_rgb(&normalize_hex(&style.foreground.as_hex()));
+    let (br, bg, bb) = hex_to_rgb(&normalize_hex(&style.background.as_hex()));
+
+    let mut codes = vec![format!("38;2;{fr};{fg};{fb}"), format!("48;2;{br};{bg};{bb}")];
+    if style.font_style.contains(giallo::FontStyle::BOLD) {
+        codes.push("1".to_string());
+    }
+    if style.font_style.contains(giallo::FontStyle::ITALIC) {
+        codes.push("3".to_string());
+    }
+    if style.font_style.contains(giallo::FontStyle::UNDERLINE) {
+        codes.push("4".to_string());
+    }
+    if style.font_style.contains(giallo::FontStyle::STRIKETHROUGH) {
+        codes.push("9".to_string());
+    }
+
+    format!("\x1b[{}m{}\x1b[0m", codes.join(";"), text)
+}
+
+/// Highlight `sample` with `theme_name` and render it as ANSI-colored text,
+/// one source line per output line.
+fn render_theme_sample(
+    registry: &Registry,
+    lang: &str,
+    theme_name: &str,
+    sample: &str,
+) -> Option<String> {
+    let options = HighlightOptions::new(lang, ThemeVariant::Single(theme_name));
+    let highlighted = registry.highlight(sample, &options).ok()?;
+
+    let mut out = String::new();
+    for line_tokens in highlighted.tokens.iter() {
+        for token in line_tokens {
+            if token.text.is_empty() {
+                continue;
+            }
+            let ThemeVariant::Single(style) = token.style else {
+                continue;
+            };
+            out.push_str(&render_ansi_token(token.text, &style));
+        }
+        out.push('\n');
+    }
+    Some(out)
+}
+
+/// `show-themes`: highlight a sample with every available theme (or, with
+/// `--theme <name>`, just that one) and print each ANSI-colored result,
+/// like delta's `show-syntax-themes`.
+fn run_show_themes(
+    registry: &Registry,
+    config: &Config,
+    lang: &str,
+    sample_path: Option<&str>,
+    theme_name: Option<&str>,
+) {
+    let resolved_lang = config.resolve_lang(lang);
+
+    let sample = match sample_path {
+        Some(path) => fs::read_to_string(path).unwrap_or_else(|err| {
+            eprintln!("failed to read {}: {}", path, err);
+            process::exit(1);
+        }),
+        None => LINT_THEME_SAMPLE.to_string(),
+    };
+
+    let mut themes = available_theme_names(registry, config);
+    if let Some(only) = theme_name {
+        themes.retain(|name| name == only);
+        if themes.is_empty() {
+            eprintln!("no such theme: {}", only);
+            process::exit(1);
+        }
+    }
+    if themes.is_empty() {
+        println!("No themes available.");
+        return;
+    }
+
+    for theme_name in themes {
+        println!("=== {} ===", theme_name);
+        match render_theme_sample(registry, &resolved_lang, &theme_name, &sample) {
+            Some(rendered) => print!("{}", rendered),
+            None => println!("  (failed to highlight with this theme)"),
+        }
+        println!();
+    }
+}
+
+/// Which custom directory an installed asset belongs in.
+#[derive(Clone, Copy, Debug, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+enum AssetKind {
+    Grammar,
+    Theme,
+}
+
+/// One file to fetch from a GitHub repo, verified against a recorded hash.
+#[derive(Clone, Debug, Deserialize)]
+struct AssetFile {
+    /// Path of the file within the repo, e.g. `"grammars/rust.json"`.
+    path: String,
+    kind: AssetKind,
+    sha256: String,
+}
+
+/// A pinned revision of a repo and the files to install from it.
+#[derive(Clone, Debug, Deserialize)]
+struct AssetEntry {
+    owner: String,
+    repo: String,
+    rev: String,
+    files: Vec<AssetFile>,
+}
+
+/// Top-level shape of an `install`/`fetch` manifest (TOML).
+#[derive(Clone, Debug, Default, Deserialize)]
+struct AssetManifest {
+    #[serde(default)]
+    assets: Vec<AssetEntry>,
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    use sha2::{Digest, Sha256};
+    let mut hasher = Sha256::new();
+    hasher.update(bytes);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Download `url`'s body into memory.
+fn fetch_asset_bytes(url: &str) -> Result<Vec<u8>, String> {
+    let response = ureq::get(url).call().map_err(|err| err.to_string())?;
+    let mut bytes = Vec::new();
+    response
+        .into_reader()
+        .read_to_end(&mut bytes)
+        .map_err(|err| err.to_string())?;
+    Ok(bytes)
+}
+
+/// Fetch, hash-verify, and write one manifest file entry into the configured
+/// `grammars_path`/`themes_path`, returning the path it was written to.
+fn install_asset_file(config: &Config, entry: &AssetEntry, file: &AssetFile) -> io::Result<PathBuf> {
+    let url = format!(
+        "https://raw.githubusercontent.com/{}/{}/{}/{}",
+        entry.owner, entry.repo, entry.rev, file.path
+    );
+
+    let bytes =
+        fetch_asset_bytes(&url).map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+
+    let digest = sha256_hex(&bytes);
+    if !digest.eq_ignore_ascii_case(&file.sha256) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!(
+                "sha256 mismatch for {} (expected {}, got {})",
+                url, file.sha256, digest
+            ),
+        ));
+    }
+
+    let configured_dir = match file.kind {
+        AssetKind::Grammar => config.grammars_path.as_deref(),
+        AssetKind::Theme => config.themes_path.as_deref(),
+    };
+    let dest_dir = configured_dir.map(expand_path).ok_or_else(|| {
+        io::Error::new(
+            io::ErrorKind::NotFound,
+            "no grammars_path/themes_path configured for this asset kind",
+        )
+    })?;
+
+    fs::create_dir_all(&dest_dir)?;
+    let file_name = Path::new(&file.path).file_name().ok_or_else(|| {
+        io::Error::new(io::ErrorKind::InvalidInput, "asset path has no file name")
+    })?;
+    let dest = dest_dir.join(file_name);
+    fs::write(&dest, &bytes)?;
+    Ok(dest)
+}
+
+/// `install`/`fetch`: read a manifest of GitHub-hosted grammar/theme assets,
+/// download and hash-verify each, and drop them into the custom directories
+/// so `load_custom_grammars`/`load_custom_themes` pick them up. Reports
+/// per-asset success/failure rather than aborting the whole batch.
+fn run_install(config: &Config, manifest_path: &str) {
+    let contents = fs::read_to_string(manifest_path).unwrap_or_else(|err| {
+        eprintln!("failed to read manifest {}: {}", manifest_path, err);
+        process::exit(1);
+    });
+    let manifest: AssetManifest = toml::from_str(&contents).unwrap_or_else(|err| {
+        eprintln!("failed to parse manifest {}: {}", manifest_path, err);
+        process::exit(1);
+    });
+
+    let mut installed = 0usize;
+    let mut failed = 0usize;
+    for entry in &manifest.assets {
+        for file in &entry.files {
+            match install_asset_file(config, entry, file) {
+                Ok(dest) => {
+                    println!(
+                        "installed {}/{}@{}:{} -> {}",
+                        entry.owner,
+                        entry.repo,
+                        entry.rev,
+                        file.path,
+                        dest.display()
+                    );
+                    installed += 1;
+                }
+                Err(err) => {
+                    eprintln!(
+                        "failed {}/{}@{}:{}: {}",
+                        entry.owner, entry.repo, entry.rev, file.path, err
+                    );
+                    failed += 1;
+                }
+            }
+        }
+    }
+
+    println!("Installed {} asset(s), {} failed.", installed, failed);
+    if failed > 0 && installed == 0 {
+        process::exit(1);
+    }
+}
+
+/// Where pinned remote grammar sources are declared, sibling to `config.toml`.
+fn grammar_manifest_path() -> PathBuf {
+    config_path().with_file_name("grammars.toml")
+}
+
+/// One pinned remote grammar source: a GitHub repo revision, the grammar
+/// file(s) to pull out of its release tarball, and the tarball's expected
+/// hash.
+#[derive(Clone, Debug, Deserialize)]
+struct GrammarSource {
+    owner: String,
+    repo: String,
+    rev: String,
+    files: Vec<String>,
+    sha256: String,
+}
+
+/// Top-level shape of `grammars.toml`: language/theme name -> pinned source.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct GrammarManifest {
+    #[serde(default)]
+    grammars: HashMap<String, GrammarSource>,
+    #[serde(default)]
+    themes: HashMap<String, GrammarSource>,
+}
+
+impl GrammarManifest {
+    fn load() -> Self {
+        let path = grammar_manifest_path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        match toml::from_str::<GrammarManifest>(&contents) {
+            Ok(manifest) => manifest,
+            Err(err) => {
+                eprintln!("grammar manifest parse error ({}): {err}", path.display());
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Path of the small cache file recording which `owner/repo@rev` has already
+/// been fetched for each language, so an unchanged manifest entry is a
+/// cache hit rather than a re-download on the next run.
+fn grammar_fetch_cache_path() -> PathBuf {
+    registry_cache_dir().join("grammar_fetch.cache")
+}
+
+fn load_grammar_fetch_cache() -> HashMap<String, String> {
+    let Ok(contents) = fs::read_to_string(grammar_fetch_cache_path()) else {
+        return HashMap::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| line.split_once('\t'))
+        .map(|(lang, key)| (lang.to_string(), key.to_string()))
+        .collect()
+}
+
+fn save_grammar_fetch_cache(cache: &HashMap<String, String>) {
+    let path = grammar_fetch_cache_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let contents: String = cache
+        .iter()
+        .map(|(lang, key)| format!("{lang}\t{key}\n"))
+        .collect();
+    if let Err(err) = fs::write(&path, contents) {
+        log::warn!("failed to write grammar fetch cache: {}", err);
+    }
+}
+
+/// Download the pinned tarball behind `source`, hash-verify it, and extract
+/// its listed file(s) into `dest_dir`. `cache_key_ns` namespaces the fetch
+/// cache entry (e.g. `"grammar:rust"` vs `"theme:rust"`) so a grammar and a
+/// theme sharing a name don't shadow each other's cache state. `force`
+/// bypasses the cache entirely, re-downloading even an entry that's already
+/// recorded as up to date. Returns the destination paths of any files
+/// actually written; an unchanged `owner/repo@rev` already recorded in the
+/// fetch cache (with those files still present on disk) is a no-op cache
+/// hit that returns an empty list.
+fn fetch_pinned_asset(
+    dest_dir: &Path,
+    cache_key_ns: &str,
+    source: &GrammarSource,
+    force: bool,
+) -> Result<Vec<PathBuf>, String> {
+    let cache_key = format!("{}/{}@{}", source.owner, source.repo, source.rev);
+    let mut cache = load_grammar_fetch_cache();
+    let already_fetched = !force
+        && cache.get(cache_key_ns) == Some(&cache_key)
+        && source.files.iter().all(|wanted| {
+            Path::new(wanted)
+                .file_name()
+                .map(|name| dest_dir.join(name).exists())
+                .unwrap_or(false)
+        });
+    if already_fetched {
+        log::debug!("fetch: {} already at {} (cache hit)", cache_key_ns, cache_key);
+        return Ok(Vec::new());
+    }
+
+    let url = format!(
+        "https://codeload.github.com/{}/{}/tar.gz/{}",
+        source.owner, source.repo, source.rev
+    );
+    let bytes = fetch_asset_bytes(&url)?;
+
+    let digest = sha256_hex(&bytes);
+    if !digest.eq_ignore_ascii_case(&source.sha256) {
+        return Err(format!(
+            "sha256 mismatch for {} (expected {}, got {})",
+            url, source.sha256, digest
+        ));
+    }
+
+    fs::create_dir_all(&dest_dir).map_err(|err| err.to_string())?;
+
+    let decoder = flate2::read::GzDecoder::new(bytes.as_slice());
+    let mut archive = tar::Archive::new(decoder);
+    let entries = archive.entries().map_err(|err| err.to_string())?;
+
+    let mut extracted = Vec::new();
+    for entry in entries {
+        let mut entry = entry.map_err(|err| err.to_string())?;
+        let entry_path = entry.path().map_err(|err| err.to_string())?.into_owned();
+        // Tarball entries are rooted in a "<repo>-<rev>/" prefix directory,
+        // so match manifest `files` against the entry's suffix.
+        let matches = source.files.iter().any(|wanted| entry_path.ends_with(wanted));
+        if !matches {
+            continue;
+        }
+
+        let file_name = entry_path
+            .file_name()
+            .ok_or_else(|| "tarball entry has no file name".to_string())?;
+        let dest = dest_dir.join(file_name);
+        entry.unpack(&dest).map_err(|err| err.to_string())?;
+        extracted.push(dest);
+    }
+
+    if extracted.is_empty() {
+        return Err(format!("no matching files found in tarball for {}", cache_key_ns));
+    }
+
+    cache.insert(cache_key_ns.to_string(), cache_key);
+    save_grammar_fetch_cache(&cache);
+    Ok(extracted)
+}
+
+/// Fetch a pinned grammar source into `config.grammars_path` (see
+/// `fetch_pinned_asset`).
+fn fetch_grammar(config: &Config, lang: &str, source: &GrammarSource, force: bool) -> Result<Vec<PathBuf>, String> {
+    let dest_dir = config
+        .grammars_path
+        .as_deref()
+        .map(expand_path)
+        .ok_or_else(|| "no grammars_path configured".to_string())?;
+    fetch_pinned_asset(&dest_dir, &format!("grammar:{lang}"), source, force)
+}
+
+/// Fetch a pinned theme source into `config.themes_path` (see
+/// `fetch_pinned_asset`).
+fn fetch_theme(config: &Config, name: &str, source: &GrammarSource, force: bool) -> Result<Vec<PathBuf>, String> {
+    let dest_dir = config
+        .themes_path
+        .as_deref()
+        .map(expand_path)
+        .ok_or_else(|| "no themes_path configured".to_string())?;
+    fetch_pinned_asset(&dest_dir, &format!("theme:{name}"), source, force)
+}
+
+/// `fetch-grammars`: eagerly fetch every grammar and theme pinned in
+/// `grammars.toml`, instead of waiting for `FETCH`/`CRAWL` to pull them in
+/// lazily on demand. `force` bypasses the fetch cache so every entry is
+/// re-downloaded even if already present.
+fn run_fetch_grammars(config: &Config, force: bool) {
+    let manifest = GrammarManifest::load();
+    if manifest.grammars.is_empty() && manifest.themes.is_empty() {
+        println!("no grammars or themes pinned in {}", grammar_manifest_path().display());
+        return;
+    }
+
+    let mut fetched = 0usize;
+    let mut up_to_date = 0usize;
+    let mut failed = 0usize;
+
+    for (lang, source) in &manifest.grammars {
+        match fetch_grammar(config, lang, source, force) {
+            Ok(paths) if paths.is_empty() => {
+                up_to_date += 1;
+                println!("grammar {lang}: already up to date");
+            }
+            Ok(paths) => {
+                fetched += 1;
+                println!("grammar {lang}: fetched {} file(s)", paths.len());
+            }
+            Err(err) => {
+                failed += 1;
+                eprintln!("grammar {lang}: failed: {err}");
+            }
+        }
+    }
+
+    for (name, source) in &manifest.themes {
+        match fetch_theme(config, name, source, force) {
+            Ok(paths) if paths.is_empty() => {
+                up_to_date += 1;
+                println!("theme {name}: already up to date");
+            }
+            Ok(paths) => {
+                fetched += 1;
+                println!("theme {name}: fetched {} file(s)", paths.len());
+            }
+            Err(err) => {
+                failed += 1;
+                eprintln!("theme {name}: failed: {err}");
+            }
+        }
+    }
+
+    println!(
+        "fetch-grammars: {fetched} fetched, {up_to_date} up to date, {failed} failed"
+    );
+    if failed > 0 {
+        process::exit(1);
+    }
+}
+
+fn token_hash(token: &str) -> String {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    token.hash(&mut hasher);
+    format!("{:x}", hasher.finish())
+}
+
+/// Hash of full buffer content, used to detect a viewport-only refresh
+/// (same text, different visible window) so it can skip re-highlighting.
+fn content_hash(text: &str) -> u64 {
+    use std::hash::{Hash, Hasher};
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    text.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Header Kakoune prepends to a FIFO message when it only wants ranges
+/// refreshed for the visible window, e.g. on scroll. A NUL-prefixed header
+/// line keeps this unambiguous from ordinary buffer text.
+const VIEWPORT_HEADER_PREFIX: &str = "\u{0}RANGE ";
+
+/// Split a FIFO message into its optional `\0RANGE <first> <last>\n`
+/// viewport header and the buffer text that follows. No header means "full
+/// buffer, no viewport hint" and the whole message is the text.
+fn parse_viewport_header(message: &str) -> (Option<(usize, usize)>, &str) {
+    let Some(rest) = message.strip_prefix(VIEWPORT_HEADER_PREFIX) else {
+        return (None, message);
+    };
+    let Some((header, text)) = rest.split_once('\n') else {
+        return (None, message);
+    };
+    let mut parts = header.split_whitespace();
+    match (parts.next().and_then(|v| v.parse().ok()), parts.next().and_then(|v| v.parse().ok())) {
+        (Some(first), Some(last)) => (Some((first, last)), text),
+        _ => (None, message),
+    }
+}
+
+/// Keep only the range entries (`"{line}.{col},{line}.{col}|{face}"`) whose
+/// line falls inside the visible window `[first, last]`.
+fn filter_ranges_to_window(ranges: &str, first: usize, last: usize) -> String {
+    ranges
+        .split_whitespace()
+        .filter(|entry| {
+            entry
+                .split(['.', ','])
+                .next()
+                .and_then(|line| line.parse::<usize>().ok())
+                .is_some_and(|line| line >= first && line <= last)
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+struct Config {
+    theme: Option<String>,
+    /// Theme to use when the terminal background is classified as dark (see
+    /// `background_is_light`), consulted by `resolve_theme` when no
+    /// explicit `theme` is set. Paired with `theme_light`.
+    #[serde(default)]
+    theme_dark: Option<String>,
+    /// Theme to use when the terminal background is classified as light.
+    /// Paired with `theme_dark`.
+    #[serde(default)]
+    theme_light: Option<String>,
+    /// Explicit override for the terminal background (`"light"` or
+    /// `"dark"`), taking priority over `COLORFGBG`/OSC 11 detection.
+    #[serde(default)]
+    background: Option<String>,
+    #[serde(default)]
+    language_map: HashMap<String, String>,
+    #[serde(default)]
+    grammars_path: Option<String>,
+    #[serde(default)]
+    themes_path: Option<String>,
+    /// How long a buffer worker waits for more edits to land on the FIFO
+    /// before committing to a highlight pass, collapsing a burst of
+    /// keystrokes into a single re-highlight. Defaults to
+    /// `DEFAULT_DEBOUNCE_MS` when unset; zero disables the wait.
+    #[serde(default)]
+    debounce_ms: Option<u64>,
+    /// Minimum time between two highlight dispatches for the same buffer,
+    /// on top of `debounce_ms`'s burst coalescing — useful for steady,
+    /// evenly-paced typing where each edit lands just outside the debounce
+    /// window and would otherwise still trigger one highlight per
+    /// keystroke. Disabled (no minimum) when unset or zero.
+    #[serde(default)]
+    min_highlight_interval_ms: Option<u64>,
+    /// `[crawl]` section controlling the `CRAWL <dir>` command.
+    #[serde(default)]
+    crawl: CrawlConfig,
+    /// `[rainbow]` section controlling rainbow delimiter highlighting.
+    #[serde(default)]
+    rainbow: RainbowConfig,
+    /// `[highlight]` section toggling coarse highlighting categories.
+    #[serde(default)]
+    highlight: HighlightConfig,
+    /// `[semantic]` section controlling semantic modifier highlighting.
+    #[serde(default)]
+    semantic: SemanticConfig,
+}
+
+/// Default debounce window (see `Config::debounce_ms`) when the config file
+/// doesn't set one.
+const DEFAULT_DEBOUNCE_MS: u64 = 30;
+
+/// Settings for the `CRAWL <dir>` command, which pre-highlights a project's
+/// files into an in-memory cache so a later INIT/H for one of them is
+/// immediate.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct CrawlConfig {
+    /// Crawl every file, not just ones with a recognized grammar alias.
+    /// Defaults to `false` when unset. `Option` (rather than a plain `bool`)
+    /// so `merge_from` can tell "not set in this file" apart from
+    /// "explicitly false", letting a nearer project config opt back out of
+    /// a global config's `all_files = true`.
+    #[serde(default)]
+    all_files: Option<bool>,
+    /// Stop crawling once this many files have been cached.
+    #[serde(default)]
+    max_files: Option<usize>,
+    /// Memory budget, in bytes, for cached highlight output. The cache
+    /// evicts least-recently-used entries once this is exceeded.
+    #[serde(default)]
+    max_cache_bytes: Option<usize>,
 }
 
-fn token_hash(token: &str) -> String {
-    use std::hash::{Hash, Hasher};
-    let mut hasher = std::collections::hash_map::DefaultHasher::new();
-    token.hash(&mut hasher);
-    format!("{:x}", hasher.finish())
+/// Default cap on the number of files a `CRAWL` will cache (see
+/// `CrawlConfig::max_files`).
+const DEFAULT_CRAWL_MAX_FILES: usize = 2000;
+/// Default memory budget for the crawl cache, in bytes (see
+/// `CrawlConfig::max_cache_bytes`).
+const DEFAULT_CRAWL_MAX_CACHE_BYTES: usize = 64 * 1024 * 1024;
+
+/// Settings for opt-in rainbow delimiter highlighting: brackets/parens are
+/// tagged with `giallo_rainbow_<depth mod color_count>` instead of their
+/// theme scope, so nesting depth is visible independent of the theme. Off by
+/// default — themes aren't guaranteed to define the `giallo_rainbow_*` faces,
+/// so enabling this is an explicit opt-in via `config.toml`.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct RainbowConfig {
+    /// Enable rainbow delimiter faces alongside the normal theme faces.
+    #[serde(default)]
+    enabled: bool,
+    /// Number of distinct rainbow colors to cycle through by nesting depth.
+    /// Defaults to `DEFAULT_RAINBOW_COLORS` when unset or zero.
+    #[serde(default)]
+    color_count: Option<usize>,
 }
 
+/// Default number of distinct rainbow colors (see `RainbowConfig::color_count`).
+const DEFAULT_RAINBOW_COLORS: usize = 6;
+
+/// Coarse on/off toggles for categories of highlighting, in the spirit of
+/// rust-analyzer's `HighlightConfig`. `strings`, `punctuation` and
+/// `operators` default to `true` (unset means "current behavior,
+/// unchanged"); `specialize` defaults to `false`.
+///
+/// Caveat: `giallo::HighlightedCode` only hands this binary a resolved
+/// [`giallo::Style`] per token, not the capture/scope name (`string`,
+/// `punctuation.delimiter`, ...) that produced it — that classification
+/// lives entirely inside the external `giallo` crate's tree-sitter query
+/// layer, which this repo doesn't have access to. So `strings` here is a
+/// text heuristic (`is_quoted_string_token`: a token is a string if it's
+/// wrapped in matching quote characters) rather than a real capture-category
+/// filter, and won't catch interpolated or multi-token strings the grammar
+/// splits across several tokens. `punctuation`/`operators`/`specialize` are
+/// genuinely text-based (a fixed list of punctuation/operator substrings in
+/// `PUNCTUATION_TOKENS`/`OPERATOR_TOKENS`), so they aren't subject to that
+/// same caveat.
 #[derive(Clone, Debug, Default, Deserialize)]
-struct Config {
-    theme: Option<String>,
+struct HighlightConfig {
     #[serde(default)]
-    language_map: HashMap<String, String>,
+    strings: Option<bool>,
     #[serde(default)]
-    grammars_path: Option<String>,
+    punctuation: Option<bool>,
     #[serde(default)]
-    themes_path: Option<String>,
+    operators: Option<bool>,
+    /// Give recognized punctuation/operator tokens their own faces (e.g.
+    /// `.`, `::`, `->`) instead of sharing the theme's generic punctuation/
+    /// operator style. See `specialized_highlight_faces`.
+    #[serde(default)]
+    specialize: Option<bool>,
+}
+
+/// Settings for opt-in semantic modifier highlighting: literal keyword
+/// sequences (`let mut NAME`, `fn NAME`, `unsafe`) get a dedicated face
+/// layered on top of the theme's normal scope face, in the spirit of
+/// rust-analyzer's `tags.rs`/`HlTag` distinction between mutable bindings,
+/// unsafe contexts, and definition sites. Off by default, same reasoning as
+/// `RainbowConfig`: themes aren't guaranteed to define these faces.
+///
+/// Caveat: like `HighlightConfig`, this only has resolved token text to work
+/// with, not real capture names (`@variable.mutable`, `@function.
+/// definition`) — those live in the external `giallo` crate's tree-sitter
+/// query layer. `scan_semantic_modifiers` is therefore a flat keyword-
+/// sequence scan over each line's reconstructed text, not a semantic
+/// analysis: it catches the common `let mut x = ...`/`fn name(...)` surface
+/// forms and bare `unsafe` keyword occurrences, but not `for mut x in ...`,
+/// closures, destructuring patterns, or distinguishing a definition site
+/// from a usage of the same name elsewhere — and it marks only the `unsafe`
+/// keyword itself, not every token inside the block it introduces.
+#[derive(Clone, Debug, Default, Deserialize)]
+struct SemanticConfig {
+    /// Enable semantic modifier faces alongside the normal theme faces.
+    #[serde(default)]
+    enabled: bool,
 }
 
 impl Config {
+    /// Zero-arg entry point: resolve project-local config discovery from
+    /// the real working directory (see `load_from`).
     fn load() -> Self {
-        let path = config_path();
-        let Ok(contents) = fs::read_to_string(&path) else {
-            return Self::default();
-        };
+        let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        Self::load_from(&cwd)
+    }
+
+    /// Load the global config (`config_path()`), then layer project-local
+    /// config over it the way cargo resolves `.cargo/config.toml`: walk
+    /// upward from `start_dir` to the filesystem root collecting
+    /// `.giallo.kak.toml` and `giallo.kak/config.toml` files, then merge
+    /// them field-by-field (see `merge_from`) furthest-first so a file
+    /// nearer to `start_dir` wins over one further up, and the global
+    /// config only fills in whatever no project file set.
+    fn load_from(start_dir: &Path) -> Self {
+        let mut config = Self::load_file(&config_path()).unwrap_or_default();
+
+        let mut project_configs = Vec::new();
+        let mut dir = Some(start_dir.to_path_buf());
+        while let Some(d) = dir {
+            for candidate in [d.join(".giallo.kak.toml"), d.join("giallo.kak/config.toml")] {
+                if let Some(found) = Self::load_file(&candidate) {
+                    project_configs.push(found);
+                }
+            }
+            dir = d.parent().map(Path::to_path_buf);
+        }
+
+        // `project_configs` is nearest-first (closest directory found
+        // first); merge furthest-first so the nearest file's fields win
+        // last and take priority.
+        for project in project_configs.into_iter().rev() {
+            config.merge_from(project);
+        }
+
+        config
+    }
+
+    fn load_file(path: &Path) -> Option<Self> {
+        let contents = fs::read_to_string(path).ok()?;
         match toml::from_str::<Config>(&contents) {
-            Ok(config) => config,
+            Ok(config) => Some(config),
             Err(err) => {
                 eprintln!("config parse error ({}): {err}", path.display());
-                Self::default()
+                None
             }
         }
     }
 
+    /// Merge `other` over `self` in place. Scalar fields are last-writer-
+    /// wins: an `other` field that's set overrides `self`'s. `language_map`
+    /// is merged key-by-key instead, so a nearer project config can
+    /// override or add a single language mapping without discarding the
+    /// rest of the map inherited from the global config. The plain-`bool`
+    /// `rainbow.enabled` and `semantic.enabled` flags are only ever turned
+    /// on by a merge: an `other` that leaves them `false` keeps `self`'s.
+    fn merge_from(&mut self, other: Config) {
+        if other.theme.is_some() {
+            self.theme = other.theme;
+        }
+        if other.theme_dark.is_some() {
+            self.theme_dark = other.theme_dark;
+        }
+        if other.theme_light.is_some() {
+            self.theme_light = other.theme_light;
+        }
+        if other.background.is_some() {
+            self.background = other.background;
+        }
+        if other.grammars_path.is_some() {
+            self.grammars_path = other.grammars_path;
+        }
+        if other.themes_path.is_some() {
+            self.themes_path = other.themes_path;
+        }
+        if other.debounce_ms.is_some() {
+            self.debounce_ms = other.debounce_ms;
+        }
+        if other.min_highlight_interval_ms.is_some() {
+            self.min_highlight_interval_ms = other.min_highlight_interval_ms;
+        }
+        self.language_map.extend(other.language_map);
+
+        if other.crawl.all_files.is_some() {
+            self.crawl.all_files = other.crawl.all_files;
+        }
+        if other.crawl.max_files.is_some() {
+            self.crawl.max_files = other.crawl.max_files;
+        }
+        if other.crawl.max_cache_bytes.is_some() {
+            self.crawl.max_cache_bytes = other.crawl.max_cache_bytes;
+        }
+
+        if other.rainbow.enabled {
+            self.rainbow.enabled = true;
+        }
+        if other.rainbow.color_count.is_some() {
+            self.rainbow.color_count = other.rainbow.color_count;
+        }
+
+        if other.highlight.strings.is_some() {
+            self.highlight.strings = other.highlight.strings;
+        }
+        if other.highlight.punctuation.is_some() {
+            self.highlight.punctuation = other.highlight.punctuation;
+        }
+        if other.highlight.operators.is_some() {
+            self.highlight.operators = other.highlight.operators;
+        }
+        if other.highlight.specialize.is_some() {
+            self.highlight.specialize = other.highlight.specialize;
+        }
+
+        if other.semantic.enabled {
+            self.semantic.enabled = true;
+        }
+    }
+
     fn resolve_lang(&self, lang: &str) -> String {
         self.language_map
             .get(lang)
@@ -592,16 +2489,226 @@ impl Config {
     }
 
     fn resolve_theme<'a>(&'a self, theme: &'a str) -> &'a str {
-        if theme.is_empty() {
-            self.theme.as_deref().unwrap_or(DEFAULT_THEME)
-        } else {
-            theme
+        if !theme.is_empty() {
+            return theme;
+        }
+        if let Some(explicit) = self.theme.as_deref() {
+            return explicit;
+        }
+        if let (Some(light), Some(dark)) = (self.theme_light.as_deref(), self.theme_dark.as_deref()) {
+            return if self.background_is_light() { light } else { dark };
+        }
+        DEFAULT_THEME
+    }
+
+    /// Resolve the effective terminal background for `theme_light`/
+    /// `theme_dark` selection. An explicit `background = "light"|"dark"`
+    /// config key always wins; otherwise fall back to `COLORFGBG`, then (if
+    /// that's unset too) an OSC 11 terminal query. Dark is the default when
+    /// nothing resolves, matching `DEFAULT_THEME`.
+    fn background_is_light(&self) -> bool {
+        match self.background.as_deref() {
+            Some("light") => return true,
+            Some("dark") => return false,
+            _ => {}
+        }
+        if std::env::var("COLORFGBG").is_ok() {
+            return terminal_prefers_light();
         }
+        query_terminal_background_osc11().unwrap_or(false)
+    }
+
+    /// How long a buffer worker should wait for more edits before committing
+    /// to a highlight pass (see `debounce_ms`).
+    fn debounce(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.debounce_ms.unwrap_or(DEFAULT_DEBOUNCE_MS))
+    }
+
+    /// Minimum spacing enforced between highlight dispatches for the same
+    /// buffer, on top of debouncing (see `min_highlight_interval_ms`). Zero
+    /// means no minimum is enforced.
+    fn min_highlight_interval(&self) -> std::time::Duration {
+        std::time::Duration::from_millis(self.min_highlight_interval_ms.unwrap_or(0))
+    }
+
+    /// Number of distinct rainbow colors to cycle through (see
+    /// `rainbow.color_count`), floored at 1 so a misconfigured zero can't
+    /// divide by zero when computing `depth % color_count`.
+    fn rainbow_color_count(&self) -> usize {
+        self.rainbow
+            .color_count
+            .filter(|&n| n > 0)
+            .unwrap_or(DEFAULT_RAINBOW_COLORS)
+            .max(1)
     }
 }
 
+// BLOCKED: Yukaii/giallo.kak#chunk9-1 ("Document the injection boundary and
+// share a language-fallback helper with CRAWL") is not implemented. The
+// request asks for real tree-sitter language injection: loading each
+// grammar's `injections.scm`, matching
+// `@injection.content`/`@injection.language` captures, running the injected
+// grammar over those byte ranges, offsetting its highlight ranges back into
+// the parent buffer's coordinate space, clipping to the parent node, and a
+// depth limit for nested injections. None of that is implemented here, and
+// it can't be from this crate: `giallo::Registry::highlight` hands back a
+// resolved `HighlightedCode` with per-token `Style` only — no parse tree, no
+// capture names, no query access. Query/capture-level tree-sitter APIs live
+// entirely inside the external, non-vendored `giallo` crate, which this repo
+// has no access to extend. This binary highlights a buffer with exactly one
+// grammar; embedded languages (HTML/CSS/SQL in tagged templates, JS regex
+// literals, embedded JSON) are highlighted as plain text within their parent
+// grammar's styling. Picking this back up requires `giallo` itself to grow
+// an injection-aware API first.
 const DEFAULT_THEME: &str = "catppuccin-frappe";
 
+/// Parse a resolved theme name into a `ThemeVariant`. `"light,dark"` (e.g.
+/// `theme = "catppuccin-latte,catppuccin-mocha"` in the config) requests a
+/// dual light/dark theme that adapts to the terminal background at runtime;
+/// anything else is a plain single theme name.
+fn parse_theme_spec(theme: &str) -> ThemeVariant<&str> {
+    if let Some((light, dark)) = theme.split_once(',') {
+        ThemeVariant::Dual {
+            light: light.trim(),
+            dark: dark.trim(),
+        }
+    } else {
+        ThemeVariant::Single(theme)
+    }
+}
+
+/// Parse an `auto:light-theme,dark-theme` theme spec (as accepted by `INIT`
+/// and `SET_THEME`) into its `(light, dark)` pair.
+fn parse_auto_theme_pair(spec: &str) -> Option<(String, String)> {
+    let pair = spec.strip_prefix("auto:")?;
+    let (light, dark) = pair.split_once(',')?;
+    Some((light.trim().to_string(), dark.trim().to_string()))
+}
+
+/// Port of delta's `is_light_syntax_theme`: perceived luminance of an sRGB
+/// hex color, `0.299*R + 0.587*G + 0.114*B` normalized to `0.0..=1.0`.
+fn perceived_luminance(hex: &str) -> f64 {
+    let (r, g, b) = hex_to_rgb(hex);
+    (0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64) / 255.0
+}
+
+/// Resolve `theme_name`'s default background and classify it as light when
+/// its perceived luminance exceeds a mid threshold, dark otherwise. Themes
+/// that fail to resolve are treated as dark, matching `DEFAULT_THEME`.
+fn is_light_theme(registry: &Registry, theme_name: &str) -> bool {
+    let options = HighlightOptions::new(PLAIN_GRAMMAR_NAME, ThemeVariant::Single(theme_name));
+    let Ok(highlighted) = registry.highlight("a", &options) else {
+        return false;
+    };
+    let theme = match highlighted.theme {
+        ThemeVariant::Single(theme) => theme,
+        ThemeVariant::Dual { light, .. } => light,
+    };
+    let bg = normalize_hex(&theme.default_style.background.as_hex());
+    perceived_luminance(&bg) > 0.5
+}
+
+/// Whether the terminal is running on a light background, from `COLORFGBG`
+/// (`"fg;bg"`, set by many terminal emulators). Defaults to dark, matching
+/// `DEFAULT_THEME`, when the variable is absent or unparseable.
+fn terminal_prefers_light() -> bool {
+    let Ok(colorfgbg) = std::env::var("COLORFGBG") else {
+        return false;
+    };
+    match colorfgbg.rsplit(';').next() {
+        Some("0") | Some("8") => false,
+        Some(_) => true,
+        None => false,
+    }
+}
+
+/// Query the terminal's background color via an OSC 11 escape
+/// (`\e]11;?\a`), parse the `rgb:RRRR/GGGG/BBBB` reply, and classify it by
+/// perceived luminance. Returns `None` if stdin/stdout aren't a TTY, the
+/// terminal doesn't answer before the ~200ms timeout, or the reply doesn't
+/// parse; callers should fall back to `COLORFGBG`/a default guess in that
+/// case. Used as a last resort by `Config::background_is_light` when
+/// `COLORFGBG` isn't set.
+fn query_terminal_background_osc11() -> Option<bool> {
+    use std::io::IsTerminal;
+    if !io::stdin().is_terminal() || !io::stdout().is_terminal() {
+        return None;
+    }
+
+    let stdin_fd = 0;
+    let mut original: libc::termios = unsafe { std::mem::zeroed() };
+    if unsafe { libc::tcgetattr(stdin_fd, &mut original) } != 0 {
+        return None;
+    }
+
+    let mut raw = original;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 0;
+    raw.c_cc[libc::VTIME] = 2; // tenths of a second; ~200ms read timeout
+
+    if unsafe { libc::tcsetattr(stdin_fd, libc::TCSANOW, &raw) } != 0 {
+        return None;
+    }
+
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x1b]11;?\x07");
+    let _ = stdout.flush();
+
+    let mut response = Vec::new();
+    let mut buf = [0u8; 64];
+    let mut stdin = io::stdin();
+    // VTIME makes each read() return promptly even if the terminal never
+    // answers; a few attempts cover a reply split across multiple writes.
+    for _ in 0..5 {
+        match stdin.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => {
+                response.extend_from_slice(&buf[..n]);
+                if response.contains(&0x07) || response.windows(2).any(|w| w == b"\x1b\\") {
+                    break;
+                }
+            }
+            Err(_) => break,
+        }
+    }
+
+    unsafe { libc::tcsetattr(stdin_fd, libc::TCSANOW, &original) };
+
+    let text = String::from_utf8_lossy(&response);
+    let rgb = text.split("rgb:").nth(1)?;
+    let mut channels = rgb.split(['/', '\x07', '\x1b']).filter(|s| !s.is_empty());
+    let r = u16::from_str_radix(channels.next()?, 16).ok()?;
+    let g = u16::from_str_radix(channels.next()?, 16).ok()?;
+    let b = u16::from_str_radix(channels.next()?, 16).ok()?;
+
+    let luminance = (0.299 * r as f64 + 0.587 * g as f64 + 0.114 * b as f64) / 65535.0;
+    Some(luminance > 0.5)
+}
+
+/// Pick whichever of `(light, dark)` matches the terminal's current
+/// background, classifying each candidate by its own luminance rather than
+/// trusting the caller's naming/order.
+fn resolve_auto_theme(registry: &Registry, pair: &(String, String)) -> String {
+    let (a, b) = pair;
+    let a_is_light = is_light_theme(registry, a);
+    let b_is_light = is_light_theme(registry, b);
+    let want_light = terminal_prefers_light();
+
+    if want_light == a_is_light {
+        a.clone()
+    } else if want_light == b_is_light {
+        b.clone()
+    } else {
+        // Neither candidate matches the desired variant (e.g. both
+        // classified the same); fall back to the positional convention.
+        if want_light {
+            a.clone()
+        } else {
+            b.clone()
+        }
+    }
+}
+
 /// Expand ~ to home directory in path
 fn expand_path(path: &str) -> PathBuf {
     if path.starts_with("~/") {
@@ -669,10 +2776,154 @@ fn add_grammar_aliases(registry: &mut Registry, meta: &GrammarMeta, path: &Path)
     }
 }
 
+/// One entry of the persisted content-hash cache consulted by
+/// `load_custom_grammars_with_cache`: the SHA-256 of a grammar file's bytes
+/// the last time it was successfully loaded, plus what that load produced,
+/// so an unchanged file can skip `Registry::add_grammar_from_path` entirely
+/// on a reload while its aliases are still restored.
+#[derive(Clone)]
+struct GrammarCacheEntry {
+    hash: String,
+    grammar_name: String,
+    aliases: Vec<String>,
+}
+
+fn grammar_content_cache_path() -> PathBuf {
+    registry_cache_dir().join("grammar_content.cache")
+}
+
+fn load_grammar_content_cache() -> HashMap<PathBuf, GrammarCacheEntry> {
+    let Ok(contents) = fs::read_to_string(grammar_content_cache_path()) else {
+        return HashMap::new();
+    };
+    contents
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split('\t');
+            let path = fields.next()?;
+            let hash = fields.next()?;
+            let grammar_name = fields.next()?;
+            let aliases = fields.next().unwrap_or("");
+            Some((
+                PathBuf::from(path),
+                GrammarCacheEntry {
+                    hash: hash.to_string(),
+                    grammar_name: grammar_name.to_string(),
+                    aliases: if aliases.is_empty() {
+                        Vec::new()
+                    } else {
+                        aliases.split(',').map(str::to_string).collect()
+                    },
+                },
+            ))
+        })
+        .collect()
+}
+
+fn save_grammar_content_cache(cache: &HashMap<PathBuf, GrammarCacheEntry>) {
+    let path = grammar_content_cache_path();
+    if let Some(parent) = path.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let contents: String = cache
+        .iter()
+        .map(|(path, entry)| {
+            format!(
+                "{}\t{}\t{}\t{}\n",
+                path.display(),
+                entry.hash,
+                entry.grammar_name,
+                entry.aliases.join(",")
+            )
+        })
+        .collect();
+    if let Err(err) = fs::write(&path, contents) {
+        log::warn!("failed to write grammar content cache: {}", err);
+    }
+}
+
+/// Aggregate outcome of a `load_custom_grammars`/`reload_custom_grammars`
+/// call, so a caller can log e.g. "12 succeeded, 1 failed in 340ms" without
+/// re-deriving it from `reparsed`.
+struct GrammarLoadSummary {
+    total: usize,
+    succeeded: usize,
+    failed: usize,
+    total_time: std::time::Duration,
+    /// Files actually reparsed this call (as opposed to skipped because
+    /// `skip_unchanged` found a matching content hash).
+    reparsed: Vec<PathBuf>,
+}
+
+/// Per-grammar job log consulted by `load_custom_grammars_in_dir` when
+/// `GIALLO_GRAMMAR_JOBLOG` is set: one tab-separated record appended (and
+/// flushed) after every grammar file is processed, so a crash mid-scan
+/// still leaves a usable partial log. Columns: sequence number, file path,
+/// byte size, parse duration (ms), resolved grammar name, number of
+/// aliases added, and exit status (`ok`, `skipped`, or `error: <message>`).
+struct GrammarJoblog {
+    file: fs::File,
+    seq: usize,
+}
+
+impl GrammarJoblog {
+    fn open(path: &Path) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self { file, seq: 0 })
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn record(
+        &mut self,
+        path: &Path,
+        byte_size: u64,
+        duration: std::time::Duration,
+        grammar_name: &str,
+        alias_count: usize,
+        status: &str,
+    ) {
+        self.seq += 1;
+        let line = format!(
+            "{}\t{}\t{}\t{:.3}\t{}\t{}\t{}\n",
+            self.seq,
+            path.display(),
+            byte_size,
+            duration.as_secs_f64() * 1000.0,
+            grammar_name,
+            alias_count,
+            status
+        );
+        if let Err(err) = self.file.write_all(line.as_bytes()) {
+            log::warn!("failed to write grammar joblog line: {}", err);
+            return;
+        }
+        if let Err(err) = self.file.flush() {
+            log::warn!("failed to flush grammar joblog: {}", err);
+        }
+    }
+}
+
+fn grammar_joblog() -> Option<GrammarJoblog> {
+    let path = std::env::var("GIALLO_GRAMMAR_JOBLOG").ok()?;
+    match GrammarJoblog::open(Path::new(&path)) {
+        Ok(joblog) => Some(joblog),
+        Err(err) => {
+            log::warn!("failed to open grammar joblog {}: {}", path, err);
+            None
+        }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 fn load_custom_grammars_in_dir(
     registry: &mut Registry,
     dir: &Path,
-    loaded_count: &mut usize,
+    cache: &mut HashMap<PathBuf, GrammarCacheEntry>,
+    skip_unchanged: bool,
+    reparsed: &mut Vec<PathBuf>,
+    succeeded: &mut usize,
+    failed: &mut usize,
+    joblog: &mut Option<GrammarJoblog>,
 ) -> io::Result<()> {
     let entries = fs::read_dir(dir)?;
 
@@ -681,7 +2932,16 @@ fn load_custom_grammars_in_dir(
         let path = entry.path();
 
         if path.is_dir() {
-            load_custom_grammars_in_dir(registry, &path, loaded_count)?;
+            load_custom_grammars_in_dir(
+                registry,
+                &path,
+                cache,
+                skip_unchanged,
+                reparsed,
+                succeeded,
+                failed,
+                joblog,
+            )?;
             continue;
         }
 
@@ -689,17 +2949,95 @@ fn load_custom_grammars_in_dir(
             continue;
         }
 
+        let started = std::time::Instant::now();
+        let bytes = match fs::read(&path) {
+            Ok(bytes) => bytes,
+            Err(err) => {
+                log::error!("failed to read grammar {}: {}", path.display(), err);
+                *failed += 1;
+                if let Some(joblog) = joblog {
+                    joblog.record(&path, 0, started.elapsed(), "", 0, &format!("error: {}", err));
+                }
+                continue;
+            }
+        };
+        let byte_size = bytes.len() as u64;
+        let hash = sha256_hex(&bytes);
+
+        if skip_unchanged {
+            if let Some(cached) = cache.get(&path) {
+                if cached.hash == hash {
+                    log::debug!("grammar unchanged, skipping reparse: {}", path.display());
+                    for alias in &cached.aliases {
+                        if !cached.grammar_name.is_empty() {
+                            registry.add_alias(&cached.grammar_name, alias);
+                        }
+                    }
+                    *succeeded += 1;
+                    if let Some(joblog) = joblog {
+                        joblog.record(
+                            &path,
+                            byte_size,
+                            started.elapsed(),
+                            &cached.grammar_name,
+                            cached.aliases.len(),
+                            "skipped",
+                        );
+                    }
+                    continue;
+                }
+            }
+        }
+
         log::debug!("loading grammar from: {}", path.display());
         match registry.add_grammar_from_path(&path) {
             Ok(_) => {
                 log::info!("loaded grammar: {}", path.display());
-                *loaded_count += 1;
-                if let Some(meta) = load_grammar_meta(&path) {
-                    add_grammar_aliases(registry, &meta, &path);
+                *succeeded += 1;
+                reparsed.push(path.clone());
+
+                let meta = load_grammar_meta(&path);
+                let mut aliases = Vec::new();
+                if let Some(meta) = &meta {
+                    add_grammar_aliases(registry, meta, &path);
+                    aliases.extend(meta.file_types.iter().cloned());
+                }
+                if let Some(stem) = file_stem_alias(&path) {
+                    aliases.push(stem);
                 }
+                let grammar_name = meta.map(|m| m.name.trim().to_string()).unwrap_or_default();
+                if let Some(joblog) = joblog {
+                    joblog.record(
+                        &path,
+                        byte_size,
+                        started.elapsed(),
+                        &grammar_name,
+                        aliases.len(),
+                        "ok",
+                    );
+                }
+                cache.insert(
+                    path,
+                    GrammarCacheEntry {
+                        hash,
+                        grammar_name,
+                        aliases,
+                    },
+                );
             }
             Err(err) => {
                 log::error!("failed to load grammar {}: {}", path.display(), err);
+                *failed += 1;
+                if let Some(joblog) = joblog {
+                    joblog.record(
+                        &path,
+                        byte_size,
+                        started.elapsed(),
+                        "",
+                        0,
+                        &format!("error: {}", err),
+                    );
+                }
             }
         }
     }
@@ -707,26 +3045,203 @@ fn load_custom_grammars_in_dir(
     Ok(())
 }
 
-fn load_custom_grammars(registry: &mut Registry, grammars_path: &str) -> io::Result<()> {
+fn load_custom_grammars(registry: &mut Registry, grammars_path: &str) -> io::Result<Vec<PathBuf>> {
+    load_custom_grammars_with_cache(registry, grammars_path, false).map(|summary| summary.reparsed)
+}
+
+/// As `load_custom_grammars`, but when `skip_unchanged` is set (used by the
+/// watch/`RELOAD` reload paths, where `registry` already has these grammars
+/// loaded from an earlier call in this same process), a grammar file whose
+/// content hash matches the persisted cache skips
+/// `Registry::add_grammar_from_path` entirely, only restoring its aliases
+/// from the cached entry. Returns an aggregate summary (total files,
+/// succeeded, failed, total time, and the files actually reparsed), so
+/// callers can log e.g. "loaded N, skipped M (unchanged)". When
+/// `GIALLO_GRAMMAR_JOBLOG` names a file, a per-grammar TSV record is also
+/// appended there (see `GrammarJoblog`) to diagnose which grammar is slow
+/// or failing without wading through scattered log lines.
+fn load_custom_grammars_with_cache(
+    registry: &mut Registry,
+    grammars_path: &str,
+    skip_unchanged: bool,
+) -> io::Result<GrammarLoadSummary> {
     let path = expand_path(grammars_path);
     let path_str = path.display().to_string();
     if !path.exists() {
         log::debug!("grammars path does not exist: {}", path_str);
-        return Ok(());
+        return Ok(GrammarLoadSummary {
+            total: 0,
+            succeeded: 0,
+            failed: 0,
+            total_time: std::time::Duration::ZERO,
+            reparsed: Vec::new(),
+        });
     }
 
-    let mut loaded_count = 0;
-    load_custom_grammars_in_dir(registry, &path, &mut loaded_count)?;
+    let started = std::time::Instant::now();
+    let mut cache = load_grammar_content_cache();
+    let mut reparsed = Vec::new();
+    let mut succeeded = 0;
+    let mut failed = 0;
+    let mut joblog = grammar_joblog();
+    load_custom_grammars_in_dir(
+        registry,
+        &path,
+        &mut cache,
+        skip_unchanged,
+        &mut reparsed,
+        &mut succeeded,
+        &mut failed,
+        &mut joblog,
+    )?;
+    save_grammar_content_cache(&cache);
+    let total_time = started.elapsed();
 
     log::info!(
-        "loaded {} custom grammars from {}",
-        loaded_count,
-        grammars_path
+        "loaded {} custom grammars from {} ({} reparsed, {} unchanged, {} failed) in {:.1}ms",
+        succeeded,
+        grammars_path,
+        reparsed.len(),
+        succeeded.saturating_sub(reparsed.len()),
+        failed,
+        total_time.as_secs_f64() * 1000.0
     );
-    Ok(())
+    Ok(GrammarLoadSummary {
+        total: succeeded + failed,
+        succeeded,
+        failed,
+        total_time,
+        reparsed,
+    })
+}
+
+/// Reload custom grammars from `grammars_path` into an already-populated
+/// `registry`, skipping files whose content hash hasn't changed since the
+/// last load (see `load_custom_grammars_with_cache`). Used by
+/// `watch_custom_assets` and the `RELOAD` protocol command.
+fn reload_custom_grammars(registry: &mut Registry, grammars_path: &str) -> io::Result<Vec<PathBuf>> {
+    load_custom_grammars_with_cache(registry, grammars_path, true).map(|summary| summary.reparsed)
 }
 
 /// Load custom themes from the given directory path
+/// Helix TOML theme scope entry: either a bare color string or a table with
+/// `fg`/`bg`/`modifiers`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum HelixScopeStyle {
+    Color(String),
+    Table {
+        fg: Option<String>,
+        bg: Option<String>,
+        #[serde(default)]
+        modifiers: Vec<String>,
+    },
+}
+
+fn helix_modifiers_to_font_style(modifiers: &[String]) -> Vec<&'static str> {
+    modifiers
+        .iter()
+        .filter_map(|m| match m.as_str() {
+            "bold" => Some("bold"),
+            "italic" => Some("italic"),
+            "underlined" => Some("underline"),
+            "crossed_out" => Some("strikethrough"),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Convert a Helix-layout TOML theme (scope -> style table) into giallo's own
+/// JSON theme schema.
+fn convert_helix_toml_theme(contents: &str) -> Option<serde_json::Value> {
+    let table: HashMap<String, HelixScopeStyle> = toml::from_str(contents).ok()?;
+
+    let mut rules = serde_json::Map::new();
+    for (scope, style) in table {
+        // Helix reserves a handful of non-scope keys for UI chrome; giallo
+        // only cares about token scopes, so every `ui.*` key is skipped.
+        if scope.starts_with("ui.") {
+            continue;
+        }
+        let mut rule = serde_json::Map::new();
+        match style {
+            HelixScopeStyle::Color(fg) => {
+                rule.insert("foreground".to_string(), serde_json::json!(fg));
+            }
+            HelixScopeStyle::Table { fg, bg, modifiers } => {
+                if let Some(fg) = fg {
+                    rule.insert("foreground".to_string(), serde_json::json!(fg));
+                }
+                if let Some(bg) = bg {
+                    rule.insert("background".to_string(), serde_json::json!(bg));
+                }
+                let font_style = helix_modifiers_to_font_style(&modifiers);
+                if !font_style.is_empty() {
+                    rule.insert(
+                        "fontStyle".to_string(),
+                        serde_json::json!(font_style.join(" ")),
+                    );
+                }
+            }
+        }
+        rules.insert(scope, serde_json::Value::Object(rule));
+    }
+
+    Some(serde_json::json!({ "scopes": serde_json::Value::Object(rules) }))
+}
+
+/// Convert a VS Code `tokenColors` theme JSON document into giallo's own
+/// theme schema.
+fn convert_vscode_theme(contents: &str) -> Option<serde_json::Value> {
+    let doc: serde_json::Value = serde_json::from_str(contents).ok()?;
+    let token_colors = doc.get("tokenColors")?.as_array()?;
+
+    let mut rules = serde_json::Map::new();
+    for entry in token_colors {
+        let scopes = match entry.get("scope") {
+            Some(serde_json::Value::String(s)) => vec![s.clone()],
+            Some(serde_json::Value::Array(arr)) => arr
+                .iter()
+                .filter_map(|v| v.as_str().map(str::to_string))
+                .collect(),
+            _ => continue,
+        };
+        let settings = entry.get("settings").cloned().unwrap_or_default();
+        let mut rule = serde_json::Map::new();
+        if let Some(fg) = settings.get("foreground").and_then(|v| v.as_str()) {
+            rule.insert("foreground".to_string(), serde_json::json!(fg));
+        }
+        if let Some(bg) = settings.get("background").and_then(|v| v.as_str()) {
+            rule.insert("background".to_string(), serde_json::json!(bg));
+        }
+        if let Some(style) = settings.get("fontStyle").and_then(|v| v.as_str()) {
+            rule.insert("fontStyle".to_string(), serde_json::json!(style));
+        }
+        for scope in scopes {
+            rules.insert(scope, serde_json::Value::Object(rule.clone()));
+        }
+    }
+
+    Some(serde_json::json!({ "scopes": serde_json::Value::Object(rules) }))
+}
+
+/// Write a converted theme document to a temp file and hand it to
+/// `Registry::add_theme_from_path`, since that's the only ingestion point
+/// the registry exposes.
+fn add_converted_theme(registry: &mut Registry, source: &Path, doc: serde_json::Value) -> io::Result<()> {
+    let name = source
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_else(|| "theme".to_string());
+    let tmp_path = std::env::temp_dir().join(format!("giallo-theme-{}-{}.json", name, process::id()));
+    fs::write(&tmp_path, serde_json::to_vec(&doc).unwrap_or_default())?;
+    let result = registry
+        .add_theme_from_path(&tmp_path)
+        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()));
+    let _ = fs::remove_file(&tmp_path);
+    result
+}
+
 fn load_custom_themes(registry: &mut Registry, themes_path: &str) -> io::Result<()> {
     let path = expand_path(themes_path);
     let path_str = path.display().to_string();
@@ -751,28 +3266,153 @@ fn load_custom_themes(registry: &mut Registry, themes_path: &str) -> io::Result<
             continue;
         }
 
-        if !path.is_file() {
-            continue;
+        if !path.is_file() {
+            continue;
+        }
+
+        let ext = path.extension().and_then(|e| e.to_str());
+
+        let result = match ext {
+            Some("json") => {
+                // VS Code themes carry a `tokenColors` array; giallo's own
+                // schema doesn't, so sniff the contents before dispatching.
+                let is_vscode = fs::read_to_string(&path)
+                    .map(|contents| contents.contains("\"tokenColors\""))
+                    .unwrap_or(false);
+                if is_vscode {
+                    fs::read_to_string(&path)
+                        .ok()
+                        .and_then(|contents| convert_vscode_theme(&contents))
+                        .ok_or_else(|| {
+                            io::Error::new(io::ErrorKind::InvalidData, "invalid vscode theme")
+                        })
+                        .and_then(|doc| add_converted_theme(registry, &path, doc))
+                } else {
+                    registry
+                        .add_theme_from_path(&path)
+                        .map_err(|err| io::Error::new(io::ErrorKind::Other, err.to_string()))
+                }
+            }
+            Some("toml") => fs::read_to_string(&path)
+                .ok()
+                .and_then(|contents| convert_helix_toml_theme(&contents))
+                .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid helix theme"))
+                .and_then(|doc| add_converted_theme(registry, &path, doc)),
+            _ => continue,
+        };
+
+        match result {
+            Ok(()) => {
+                loaded_count += 1;
+                log::debug!("loaded custom theme from {:?}", path);
+            }
+            Err(err) => {
+                log::warn!("failed to load theme from {:?}: {}", path, err);
+            }
+        }
+    }
+
+    log::info!("loaded {} custom themes from {}", loaded_count, themes_path);
+    Ok(())
+}
+
+/// How often `watch_custom_assets` re-checks the watched directories.
+const WATCH_POLL_INTERVAL: std::time::Duration = std::time::Duration::from_millis(100);
+/// How long to let a detected change settle before reloading, so a burst of
+/// writes (an editor atomically replacing a file, or several files saved
+/// together) collapses into a single reload instead of one per touched file.
+const WATCH_DEBOUNCE: std::time::Duration = std::time::Duration::from_millis(200);
+
+/// Fingerprint every file under `dir` (recursively) as `(path, len, mtime)`
+/// tuples — the same shape `registry_cache_key` already hashes for cache
+/// invalidation — so a changed tree can be detected without re-reading file
+/// contents.
+fn fingerprint_dir(dir: &Path) -> Vec<(PathBuf, u64, Option<std::time::SystemTime>)> {
+    let mut entries = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let Ok(read_dir) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in read_dir.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            if let Ok(meta) = entry.metadata() {
+                entries.push((path, meta.len(), meta.modified().ok()));
+            }
+        }
+    }
+    entries.sort_by(|a, b| a.0.cmp(&b.0));
+    entries
+}
+
+/// Long-lived watch loop that hot-reloads `grammars_path`/`themes_path` into
+/// `registry` as their files change, without restarting the server — the
+/// editor-side analogue of a `--watch` loop, driven off the `INIT`/`H`
+/// protocol's same `Config` paths. Polls file fingerprints rather than
+/// subscribing to OS file events, since this tree has no event-watcher
+/// dependency to build on and `registry_cache_key` already fingerprints
+/// these same directories for cache invalidation. A burst of changes within
+/// `WATCH_DEBOUNCE` of the first detected one collapses into a single
+/// reload; a file that fails to parse is simply skipped by
+/// `load_custom_grammars`/`load_custom_themes` (both log and move on), so a
+/// half-saved file can't clobber what's already loaded for it. Runs until
+/// `quit` is set, so the server's shutdown also stops the watcher.
+fn watch_custom_assets(
+    registry: Arc<Mutex<Registry>>,
+    grammars_path: Option<String>,
+    themes_path: Option<String>,
+    quit: Arc<AtomicBool>,
+) {
+    let watched_dirs: Vec<PathBuf> = [&grammars_path, &themes_path]
+        .into_iter()
+        .flatten()
+        .map(|p| expand_path(p))
+        .filter(|p| p.exists())
+        .collect();
+    if watched_dirs.is_empty() {
+        return;
+    }
+
+    let mut last_fingerprint: Vec<_> = watched_dirs.iter().map(|d| fingerprint_dir(d)).collect();
+
+    while !quit.load(Ordering::Relaxed) {
+        thread::sleep(WATCH_POLL_INTERVAL);
+        if quit.load(Ordering::Relaxed) {
+            break;
         }
 
-        // Check if it's a JSON file
-        if path.extension().and_then(|e| e.to_str()) != Some("json") {
+        let changed: Vec<_> = watched_dirs.iter().map(|d| fingerprint_dir(d)).collect();
+        if changed == last_fingerprint {
             continue;
         }
 
-        match registry.add_theme_from_path(&path) {
-            Ok(_) => {
-                loaded_count += 1;
-                log::debug!("loaded custom theme from {:?}", path);
+        thread::sleep(WATCH_DEBOUNCE);
+        let settled: Vec<_> = watched_dirs.iter().map(|d| fingerprint_dir(d)).collect();
+        last_fingerprint = settled;
+
+        let mut registry = registry.lock().unwrap();
+        if let Some(ref grammars_path) = grammars_path {
+            match reload_custom_grammars(&mut registry, grammars_path) {
+                Ok(reparsed) => log::info!(
+                    "watch: reloaded grammars from {} ({} reparsed)",
+                    grammars_path,
+                    reparsed.len()
+                ),
+                Err(err) => log::error!("watch: failed to reload grammars from {}: {}", grammars_path, err),
             }
-            Err(err) => {
-                log::warn!("failed to load theme from {:?}: {}", path, err);
+        }
+        if let Some(ref themes_path) = themes_path {
+            match load_custom_themes(&mut registry, themes_path) {
+                Ok(()) => log::info!("watch: reloaded themes from {}", themes_path),
+                Err(err) => log::error!("watch: failed to reload themes from {}: {}", themes_path, err),
             }
         }
+        registry.link_grammars();
     }
-
-    log::info!("loaded {} custom themes from {}", loaded_count, themes_path);
-    Ok(())
 }
 
 /// List all available grammars (builtin + custom)
@@ -908,50 +3548,97 @@ fn list_grammars(registry: &Registry, config: &Config) {
 }
 
 /// List all available themes (builtin + custom)
+/// Names of the themes bundled with giallo itself (not user-installed
+/// custom themes). Shared between `list_themes` and `show-themes` so both
+/// commands agree on what counts as "builtin".
+const BUILTIN_THEME_NAMES: &[&str] = &[
+    "catppuccin-frappe",
+    "catppuccin-latte",
+    "catppuccin-macchiato",
+    "catppuccin-mocha",
+    "dracula",
+    "dracula-soft",
+    "gruvbox-dark-hard",
+    "gruvbox-dark-medium",
+    "gruvbox-dark-soft",
+    "gruvbox-light-hard",
+    "gruvbox-light-medium",
+    "gruvbox-light-soft",
+    "kanagawa-dragon",
+    "kanagawa-lotus",
+    "kanagawa-wave",
+    "tokyo-night",
+    "github-dark",
+    "github-dark-default",
+    "github-dark-dimmed",
+    "github-light",
+    "github-light-default",
+    "monokai",
+    "nord",
+    "one-dark-pro",
+    "rose-pine",
+    "rose-pine-dawn",
+    "rose-pine-moon",
+    "solarized-dark",
+    "solarized-light",
+    "ayu-dark",
+    "ayu-mirage",
+    "vscode-dark",
+    "dark-plus",
+    "light-plus",
+];
+
+/// Names of custom theme files (`.json`/`.toml`) found under
+/// `config.themes_path`, stripped of extension.
+fn custom_theme_names(config: &Config) -> Vec<String> {
+    let Some(ref themes_path) = config.themes_path else {
+        return Vec::new();
+    };
+    let path = expand_path(themes_path);
+    let Ok(entries) = fs::read_dir(&path) else {
+        return Vec::new();
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|e| e.ok())
+        .filter(|e| {
+            let name = e.file_name();
+            let name_str = name.to_string_lossy();
+            !name_str.starts_with('.') && e.path().is_file()
+        })
+        .filter_map(|e| {
+            let path = e.path();
+            let ext = path.extension().and_then(|e| e.to_str());
+            if matches!(ext, Some("json") | Some("toml")) {
+                path.file_stem().map(|s| s.to_string_lossy().to_string())
+            } else {
+                None
+            }
+        })
+        .collect();
+
+    names.sort();
+    names
+}
+
+/// All theme names the registry actually knows about: builtins confirmed
+/// via `registry.contains_theme` plus any custom themes on disk.
+fn available_theme_names(registry: &Registry, config: &Config) -> Vec<String> {
+    let mut names: Vec<String> = BUILTIN_THEME_NAMES
+        .iter()
+        .filter(|name| registry.contains_theme(name))
+        .map(|name| name.to_string())
+        .collect();
+    names.extend(custom_theme_names(config));
+    names
+}
+
 fn list_themes(registry: &Registry, config: &Config) {
     println!("Available themes:");
     println!();
 
-    // Common builtin themes
-    let common_themes = vec![
-        "catppuccin-frappe",
-        "catppuccin-latte",
-        "catppuccin-macchiato",
-        "catppuccin-mocha",
-        "dracula",
-        "dracula-soft",
-        "gruvbox-dark-hard",
-        "gruvbox-dark-medium",
-        "gruvbox-dark-soft",
-        "gruvbox-light-hard",
-        "gruvbox-light-medium",
-        "gruvbox-light-soft",
-        "kanagawa-dragon",
-        "kanagawa-lotus",
-        "kanagawa-wave",
-        "tokyo-night",
-        "github-dark",
-        "github-dark-default",
-        "github-dark-dimmed",
-        "github-light",
-        "github-light-default",
-        "monokai",
-        "nord",
-        "one-dark-pro",
-        "rose-pine",
-        "rose-pine-dawn",
-        "rose-pine-moon",
-        "solarized-dark",
-        "solarized-light",
-        "ayu-dark",
-        "ayu-mirage",
-        "vscode-dark",
-        "dark-plus",
-        "light-plus",
-    ];
-
     let mut found_themes = Vec::new();
-    for theme in &common_themes {
+    for theme in BUILTIN_THEME_NAMES {
         if registry.contains_theme(theme) {
             found_themes.push(*theme);
         }
@@ -965,40 +3652,19 @@ fn list_themes(registry: &Registry, config: &Config) {
         println!();
     }
 
-    // List custom themes from directory
+    // List custom themes from directory (both `.json` and `.toml`)
     if let Some(ref themes_path) = config.themes_path {
         let path = expand_path(themes_path);
         if path.exists() {
-            let mut custom_count = 0;
-            if let Ok(entries) = fs::read_dir(&path) {
-                let mut custom_themes: Vec<String> = entries
-                    .filter_map(|e| e.ok())
-                    .filter(|e| {
-                        let name = e.file_name();
-                        let name_str = name.to_string_lossy();
-                        !name_str.starts_with('.') && e.path().is_file()
-                    })
-                    .filter_map(|e| {
-                        let path = e.path();
-                        let ext = path.extension().and_then(|e| e.to_str());
-                        if ext == Some("json") {
-                            path.file_stem().map(|s| s.to_string_lossy().to_string())
-                        } else {
-                            None
-                        }
-                    })
-                    .collect();
+            let custom_themes = custom_theme_names(config);
+            let custom_count = custom_themes.len();
 
-                custom_themes.sort();
-                custom_count = custom_themes.len();
-
-                if custom_count > 0 {
-                    println!("Custom themes from {} ({}):", themes_path, custom_count);
-                    for theme in custom_themes {
-                        println!("  {} (custom)", theme);
-                    }
-                    println!();
+            if custom_count > 0 {
+                println!("Custom themes from {} ({}):", themes_path, custom_count);
+                for theme in custom_themes {
+                    println!("  {} (custom)", theme);
                 }
+                println!();
             }
 
             if custom_count == 0 && found_themes.is_empty() {
@@ -1031,6 +3697,113 @@ fn config_path() -> PathBuf {
     }
 }
 
+/// Directory holding the serialized `Registry` cache blob.
+fn registry_cache_dir() -> PathBuf {
+    if let Ok(dir) = std::env::var("XDG_CACHE_HOME") {
+        PathBuf::from(dir).join("giallo")
+    } else if let Ok(home) = std::env::var("HOME") {
+        PathBuf::from(home).join(".cache/giallo")
+    } else {
+        PathBuf::from(".giallo-cache")
+    }
+}
+
+fn registry_cache_path() -> PathBuf {
+    registry_cache_dir().join("registry.bin")
+}
+
+fn registry_cache_key_path() -> PathBuf {
+    registry_cache_dir().join("registry.key")
+}
+
+/// Bump when the serialized shape of `Registry` (or what we feed into it)
+/// changes in a way that isn't reflected by `CARGO_PKG_VERSION`, to force
+/// existing caches to rebuild rather than fail to deserialize.
+const REGISTRY_CACHE_SCHEMA: u32 = 1;
+
+/// Build a cache key from the cache schema version, the crate version, and,
+/// for each configured custom grammar/theme directory, the sorted list of
+/// file paths with their sizes and mtimes. Any change to those inputs
+/// invalidates the cache.
+fn registry_cache_key(config: &Config) -> String {
+    use std::hash::{Hash, Hasher};
+
+    let mut hasher = std::collections::hash_map::DefaultHasher::new();
+    REGISTRY_CACHE_SCHEMA.hash(&mut hasher);
+    env!("CARGO_PKG_VERSION").hash(&mut hasher);
+
+    for dir in [&config.grammars_path, &config.themes_path]
+        .into_iter()
+        .flatten()
+    {
+        let path = expand_path(dir);
+        let mut entries: Vec<(String, u64, Option<std::time::SystemTime>)> = Vec::new();
+        if let Ok(read_dir) = fs::read_dir(&path) {
+            for entry in read_dir.flatten() {
+                if let Ok(meta) = entry.metadata() {
+                    entries.push((
+                        entry.path().display().to_string(),
+                        meta.len(),
+                        meta.modified().ok(),
+                    ));
+                }
+            }
+        }
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (path, size, mtime) in entries {
+            path.hash(&mut hasher);
+            size.hash(&mut hasher);
+            mtime.hash(&mut hasher);
+        }
+    }
+
+    format!("{:x}", hasher.finish())
+}
+
+/// Load a previously cached `Registry` if its stored key matches `key`.
+fn load_cached_registry(key: &str) -> Option<Registry> {
+    let stored_key = fs::read_to_string(registry_cache_key_path()).ok()?;
+    if stored_key.trim() != key {
+        log::debug!("registry cache: key mismatch, rebuilding");
+        return None;
+    }
+    let bytes = match fs::read(registry_cache_path()) {
+        Ok(bytes) => bytes,
+        Err(err) => {
+            log::debug!("registry cache: no cached blob ({}), rebuilding", err);
+            return None;
+        }
+    };
+    match bincode::deserialize(&bytes) {
+        Ok(registry) => Some(registry),
+        Err(err) => {
+            log::warn!("registry cache: failed to deserialize cached blob, rebuilding: {}", err);
+            None
+        }
+    }
+}
+
+/// Serialize `registry` to the cache blob alongside its cache key.
+fn write_registry_cache(key: &str, registry: &Registry) {
+    let dir = registry_cache_dir();
+    if let Err(err) = fs::create_dir_all(&dir) {
+        log::warn!("failed to create registry cache dir {}: {}", dir.display(), err);
+        return;
+    }
+    match bincode::serialize(registry) {
+        Ok(bytes) => {
+            if let Err(err) = fs::write(registry_cache_path(), bytes) {
+                log::warn!("failed to write registry cache: {}", err);
+                return;
+            }
+            if let Err(err) = fs::write(registry_cache_key_path(), key) {
+                log::warn!("failed to write registry cache key: {}", err);
+            }
+        }
+        Err(err) => log::warn!("failed to serialize registry for cache: {}", err),
+    }
+}
+
 fn create_fifo(path: &Path) -> io::Result<()> {
     let c_path = std::ffi::CString::new(path.as_os_str().to_string_lossy().as_bytes())
         .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "invalid fifo path"))?;
@@ -1094,7 +3867,7 @@ fn handle_init(token: &str, base_dir: &Path) -> io::Result<(PathBuf, String)> {
 fn run_server<R: BufRead, W: Write>(
     mut reader: R,
     mut writer: W,
-    registry: Arc<Registry>,
+    registry: Arc<Mutex<Registry>>,
     config: &Config,
     oneshot: bool,
     base_dir: Option<&Path>,
@@ -1134,6 +3907,77 @@ fn run_server<R: BufRead, W: Write>(
         let mut parts = line.split_whitespace();
         let cmd = parts.next().unwrap_or("");
 
+        if cmd == "H" {
+            let lang = parts.next().unwrap_or("").to_string();
+            let theme = parts.next().unwrap_or("").to_string();
+            let len: usize = match parts.next().and_then(|v| v.parse().ok()) {
+                Some(v) => v,
+                None => {
+                    log::error!("H: missing or invalid length");
+                    continue;
+                }
+            };
+            // Optional fourth token selects the serialization: "kakoune"
+            // (default, matches INIT/SET_THEME's live buffer commands),
+            // "html" (self-contained inline styles), or "css" (stylesheet
+            // plus classed markup).
+            let format = parts.next().unwrap_or("kakoune").to_string();
+
+            let bytes = match read_exact_bytes(&mut reader, len) {
+                Ok(bytes) => bytes,
+                Err(err) => {
+                    log::error!("H: failed to read {} byte body: {}", len, err);
+                    continue;
+                }
+            };
+            let text = String::from_utf8_lossy(&bytes).into_owned();
+
+            let resolved_lang = config.resolve_lang(&lang);
+            let resolved_theme = config.resolve_theme(&theme);
+            let reg = registry.lock().unwrap();
+            let options = HighlightOptions::new(&resolved_lang, parse_theme_spec(resolved_theme));
+            let highlighted = match reg.highlight(&text, &options) {
+                Ok(h) => h,
+                Err(err) => {
+                    log::warn!(
+                        "H: highlight failed for lang={}: {} (falling back to plain)",
+                        resolved_lang,
+                        err
+                    );
+                    let fallback =
+                        HighlightOptions::new(PLAIN_GRAMMAR_NAME, parse_theme_spec(resolved_theme));
+                    match reg.highlight(&text, &fallback) {
+                        Ok(h) => h,
+                        Err(err) => {
+                            log::error!("H: fallback also failed: {}", err);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            let output = match format.as_str() {
+                "html" => build_html_output(&highlighted),
+                "css" => build_css_output(&highlighted),
+                _ => {
+                    let (mut faces, mut ranges, theme_variant_snippet) = build_kakoune_commands(&highlighted, config);
+                    apply_rainbow(config, &highlighted, &mut faces, &mut ranges);
+                    apply_semantic_modifiers(config, &highlighted, &mut faces, &mut ranges);
+                    let mut commands = build_commands(&faces, &ranges);
+                    if let Some(snippet) = theme_variant_snippet {
+                        commands.push_str(&snippet);
+                    }
+                    commands
+                }
+            };
+            drop(reg);
+
+            if let Err(err) = write_response(&mut writer, &output) {
+                log::error!("H: failed to write response: {}", err);
+            }
+            continue;
+        }
+
         if cmd == "INIT" {
             let session = match parts.next() {
                 Some(v) => v.to_string(),
@@ -1160,16 +4004,24 @@ fn run_server<R: BufRead, W: Write>(
                 }
             };
             let lang = parts.next().unwrap_or("").to_string();
-            let theme = parts.next().unwrap_or("").to_string();
+            let raw_theme = parts.next().unwrap_or("").to_string();
+            let file_path = parts.next().map(|v| v.to_string());
             log::debug!(
-                "INIT: session={} buffer={} token={} lang={} theme={}",
+                "INIT: session={} buffer={} token={} lang={} theme={} file_path={:?}",
                 session,
                 buffer,
                 token,
                 lang,
-                theme
+                raw_theme,
+                file_path
             );
 
+            let auto_pair = parse_auto_theme_pair(&raw_theme);
+            let theme = match &auto_pair {
+                Some(pair) => resolve_auto_theme(&registry.lock().unwrap(), pair),
+                None => raw_theme,
+            };
+
             let Some(base_dir) = base_dir else {
                 log::error!("INIT: init not supported in this mode");
                 eprintln!("init not supported in this mode");
@@ -1199,19 +4051,39 @@ fn run_server<R: BufRead, W: Write>(
             let req_path = req.clone();
             let token_clone = token.clone();
             let config_clone = config.clone();
+            let crawled = file_path.as_ref().and_then(|p| {
+                let canonical = fs::canonicalize(p).ok()?;
+                let entry = resources.crawl_cache.lock().unwrap().get(&canonical)?;
+                if (lang.is_empty() || lang == entry.lang) && theme == entry.theme {
+                    Some(entry)
+                } else {
+                    None
+                }
+            });
             let ctx = BufferContext::new(
                 session.clone(),
                 buffer.clone(),
                 sentinel.clone(),
                 lang.clone(),
                 theme.clone(),
-            );
+            )
+            .with_file_path(file_path)
+            .with_auto_theme_pair(auto_pair);
             // Clone for storage in map (before moving to thread)
             let ctx_for_map = ctx.clone();
+            // `ctx` itself is moved into the buffer thread below and
+            // `ctx_for_map` into `buffer_contexts`, but the two `send_to_kak`
+            // calls just below that still need the persistent pipe handle —
+            // grab an `Arc` clone now so they can reuse (and populate) the
+            // same cached connection the buffer thread's own sends use.
+            let kak_pipe = ctx.kak_pipe.clone();
             log::debug!("INIT: spawning buffer handler thread");
             let thread_quit_flag = resources.quit_flag();
             let thread_registry = registry.clone();
-            thread::spawn(move || {
+            let worker_handles =
+                resources.register_worker(buffer.clone(), ctx.lang.clone(), ctx.theme.clone());
+            let thread_worker_handles = worker_handles.clone();
+            let join_handle = thread::spawn(move || {
                 log::debug!("buffer thread: starting for {}", token_clone);
                 log::debug!("buffer thread: using shared registry for {}", token_clone);
 
@@ -1221,24 +4093,39 @@ fn run_server<R: BufRead, W: Write>(
                     &config_clone,
                     ctx,
                     Some(&thread_quit_flag),
+                    Some(&thread_worker_handles),
                 ) {
                     Ok(_) => log::debug!("buffer thread: completed normally for {}", token_clone),
-                    Err(err) => log::error!("buffer thread: error for {}: {}", token_clone, err),
+                    Err(err) => {
+                        log::error!("buffer thread: error for {}: {}", token_clone, err);
+                        thread_worker_handles.mark_dead(err.to_string());
+                    }
                 }
 
                 let _ = fs::remove_file(&req_path);
                 log::debug!("buffer thread: exiting for {}", token_clone);
             });
+            resources.attach_join_handle(&buffer, join_handle);
 
             // Store context in map for later updates
             buffer_contexts.insert(buffer.clone(), ctx_for_map);
 
-            if let Err(err) = send_to_kak(&session, &buffer, &commands) {
+            if let Err(err) = send_to_kak(&session, &buffer, &kak_pipe, &commands) {
                 log::error!("INIT: failed to send init to kak: {}", err);
                 eprintln!("failed to send init to kak: {err}");
             } else {
                 log::debug!("INIT: sent buffer options to kak");
             }
+
+            // A prior `CRAWL` already highlighted this file for the same
+            // lang/theme — send the cached commands immediately instead of
+            // waiting for the buffer thread's first FIFO read.
+            if let Some(entry) = crawled {
+                log::debug!("INIT: buffer={} served from crawl cache", buffer);
+                if let Err(err) = send_to_kak(&session, &buffer, &kak_pipe, &entry.commands) {
+                    log::error!("INIT: failed to send cached highlight to kak: {}", err);
+                }
+            }
             continue;
         }
 
@@ -1250,7 +4137,7 @@ fn run_server<R: BufRead, W: Write>(
                     continue;
                 }
             };
-            let theme = match parts.next() {
+            let requested_theme = match parts.next() {
                 Some(v) => v.to_string(),
                 None => {
                     log::error!("SET_THEME: missing theme");
@@ -1259,15 +4146,225 @@ fn run_server<R: BufRead, W: Write>(
             };
 
             if let Some(ctx) = buffer_contexts.get(&buffer) {
+                // "auto:light,dark" records a new pair; bare "auto" re-resolves
+                // whichever pair is already stored for this buffer.
+                let resolved_theme = if let Some(pair) = parse_auto_theme_pair(&requested_theme) {
+                    *ctx.auto_theme_pair.lock().unwrap() = Some(pair.clone());
+                    resolve_auto_theme(&registry.lock().unwrap(), &pair)
+                } else if requested_theme == "auto" {
+                    match ctx.auto_theme_pair.lock().unwrap().clone() {
+                        Some(pair) => resolve_auto_theme(&registry.lock().unwrap(), &pair),
+                        None => {
+                            log::warn!(
+                                "SET_THEME: buffer={} requested auto with no stored light/dark pair",
+                                buffer
+                            );
+                            continue;
+                        }
+                    }
+                } else {
+                    requested_theme.clone()
+                };
+
                 let mut ctx_theme = ctx.theme.lock().unwrap();
-                *ctx_theme = theme.clone();
-                log::debug!("SET_THEME: updated buffer={} theme={}", buffer, theme);
+                *ctx_theme = resolved_theme.clone();
+                log::debug!(
+                    "SET_THEME: updated buffer={} theme={} (requested={})",
+                    buffer,
+                    resolved_theme,
+                    requested_theme
+                );
             } else {
                 log::warn!("SET_THEME: buffer={} not found", buffer);
             }
             continue;
         }
 
+        if cmd == "SET_LANG" {
+            let buffer = match parts.next() {
+                Some(v) => v.to_string(),
+                None => {
+                    log::error!("SET_LANG: missing buffer");
+                    continue;
+                }
+            };
+            let grammar = match parts.next() {
+                Some(v) => v.to_string(),
+                None => {
+                    log::error!("SET_LANG: missing grammar");
+                    continue;
+                }
+            };
+
+            if let Some(ctx) = buffer_contexts.get(&buffer) {
+                if !registry.lock().unwrap().contains_grammar(&grammar) {
+                    log::error!(
+                        "SET_LANG: buffer={} grammar={} not found in registry",
+                        buffer,
+                        grammar
+                    );
+                    continue;
+                }
+
+                *ctx.lang.lock().unwrap() = grammar.clone();
+                log::debug!("SET_LANG: updated buffer={} lang={}", buffer, grammar);
+
+                let text = ctx.last_text.lock().unwrap().clone();
+                if let Some(text) = text {
+                    let theme = ctx.theme.lock().unwrap().clone();
+                    // Preempt any highlight pass the buffer worker already
+                    // has in flight for the old language.
+                    let generation = ctx.generation.fetch_add(1, Ordering::Relaxed) + 1;
+                    let reg = registry.lock().unwrap();
+                    highlight_and_send(&text, &grammar, &theme, &reg, config, ctx, None, generation);
+                } else {
+                    log::debug!(
+                        "SET_LANG: buffer={} has no cached text yet, skipping re-highlight",
+                        buffer
+                    );
+                }
+            } else {
+                log::warn!("SET_LANG: buffer={} not found", buffer);
+            }
+            continue;
+        }
+
+        if cmd == "WORKERS" {
+            for worker in resources.list_workers() {
+                let state = match &worker.state {
+                    WorkerState::Idle => "idle".to_string(),
+                    WorkerState::Highlighting => "highlighting".to_string(),
+                    WorkerState::Dead { error } => format!("dead:{error}"),
+                    WorkerState::Killed => "killed".to_string(),
+                };
+                writeln!(
+                    writer,
+                    "{} {} {} {} {:.1}",
+                    worker.buffer, worker.lang, worker.theme, state, worker.idle_secs
+                )
+                .ok();
+            }
+            writeln!(writer, "END").ok();
+            writer.flush().ok();
+            continue;
+        }
+
+        if cmd == "KILL" {
+            let buffer = match parts.next() {
+                Some(v) => v.to_string(),
+                None => {
+                    log::error!("KILL: missing buffer");
+                    continue;
+                }
+            };
+            if resources.kill_worker(&buffer) {
+                log::debug!("KILL: cancel flag set for buffer={}", buffer);
+            } else {
+                log::warn!("KILL: buffer={} not found", buffer);
+            }
+            continue;
+        }
+
+        if cmd == "FETCH" {
+            let lang = match parts.next() {
+                Some(v) => v.to_string(),
+                None => {
+                    log::error!("FETCH: missing lang");
+                    continue;
+                }
+            };
+
+            let manifest = GrammarManifest::load();
+            let Some(source) = manifest.grammars.get(&lang) else {
+                log::error!("FETCH: no manifest entry for lang={}", lang);
+                continue;
+            };
+
+            match fetch_grammar(config, &lang, source, false) {
+                Ok(paths) => {
+                    let mut reg = registry.lock().unwrap();
+                    for path in &paths {
+                        match reg.add_grammar_from_path(path) {
+                            Ok(_) => {
+                                log::info!("FETCH: loaded grammar {} from {}", lang, path.display());
+                                if let Some(meta) = load_grammar_meta(path) {
+                                    add_grammar_aliases(&mut reg, &meta, path);
+                                }
+                            }
+                            Err(err) => {
+                                log::error!("FETCH: failed to register grammar {}: {}", lang, err)
+                            }
+                        }
+                    }
+                    if !paths.is_empty() {
+                        reg.link_grammars();
+                    }
+                    log::debug!("FETCH: lang={} available without restart", lang);
+                }
+                Err(err) => {
+                    log::error!(
+                        "FETCH: failed for lang={}: {} (falling back to plain)",
+                        lang,
+                        err
+                    );
+                }
+            }
+            continue;
+        }
+
+        if cmd == "CRAWL" {
+            let dir = match parts.next() {
+                Some(v) => v.to_string(),
+                None => {
+                    log::error!("CRAWL: missing directory");
+                    continue;
+                }
+            };
+
+            let dir_path = expand_path(&dir);
+            if !dir_path.is_dir() {
+                log::error!("CRAWL: {} is not a directory", dir_path.display());
+                writeln!(writer, "ERR not a directory").ok();
+                writer.flush().ok();
+                continue;
+            }
+
+            let cached = crawl_directory(&dir_path, &registry, config, resources);
+            log::info!(
+                "CRAWL: cached {} files from {} (cache now holds {})",
+                cached,
+                dir_path.display(),
+                resources.crawl_cache.lock().unwrap().len()
+            );
+            writeln!(writer, "OK {}", cached).ok();
+            writer.flush().ok();
+            continue;
+        }
+
+        if cmd == "RELOAD" {
+            // Force an immediate reload of custom grammars/themes, the same
+            // work `watch_custom_assets` does on a detected change — lets a
+            // Kakoune mapping trigger a reload on demand instead of waiting
+            // for the poll/debounce window, e.g. right after saving a theme
+            // being tweaked live.
+            let mut guard = registry.lock().unwrap();
+            if let Some(ref grammars_path) = config.grammars_path {
+                if let Err(err) = reload_custom_grammars(&mut guard, grammars_path) {
+                    log::error!("RELOAD: failed to reload grammars: {}", err);
+                }
+            }
+            if let Some(ref themes_path) = config.themes_path {
+                if let Err(err) = load_custom_themes(&mut guard, themes_path) {
+                    log::error!("RELOAD: failed to reload themes: {}", err);
+                }
+            }
+            guard.link_grammars();
+            drop(guard);
+            writeln!(writer, "OK").ok();
+            writer.flush().ok();
+            continue;
+        }
+
         eprintln!("unknown command: {cmd}");
         continue;
     }
@@ -1276,7 +4373,23 @@ fn run_server<R: BufRead, W: Write>(
 }
 
 fn main() {
-    let (mode, verbose) = parse_args();
+    let (mode, verbose) = match parse_args() {
+        Ok(parsed) => parsed,
+        Err(ArgError::Help(text)) | Err(ArgError::Version(text)) => {
+            println!("{text}");
+            process::exit(0);
+        }
+        Err(ArgError::UnknownFlag(flag)) => {
+            eprintln!("giallo-kak: unrecognized argument '{flag}'\n");
+            eprintln!("{HELP_TEXT}");
+            process::exit(2);
+        }
+        Err(ArgError::MissingValue(flag)) => {
+            eprintln!("giallo-kak: '{flag}' requires a value\n");
+            eprintln!("{HELP_TEXT}");
+            process::exit(2);
+        }
+    };
     let base_dir = std::env::temp_dir().join(format!("giallo-kak-{}", process::id()));
 
     if let Mode::KakouneRc = mode {
@@ -1292,8 +4405,18 @@ fn main() {
     log::info!("starting giallo-kak server");
     log::debug!("base_dir: {}", base_dir.display());
 
+    let config = Config::load();
+    log::debug!("config loaded: {:?}", config);
+
     // Create server resources for cleanup management
-    let resources = ServerResources::new(base_dir.clone());
+    let resources = ServerResources::new(
+        base_dir.clone(),
+        config.crawl.max_files.unwrap_or(DEFAULT_CRAWL_MAX_FILES),
+        config
+            .crawl
+            .max_cache_bytes
+            .unwrap_or(DEFAULT_CRAWL_MAX_CACHE_BYTES),
+    );
 
     // Setup signal handler for graceful shutdown
     if let Err(e) = resources.setup_signal_handler() {
@@ -1302,41 +4425,86 @@ fn main() {
         log::debug!("signal handler installed successfully");
     }
 
-    let mut registry = match Registry::builtin() {
-        Ok(registry) => registry,
-        Err(err) => {
-            log::error!("failed to load giallo registry: {err}");
-            eprintln!("failed to load giallo registry: {err}");
-            process::exit(1);
-        }
-    };
-    log::debug!("registry loaded successfully");
+    let cache_key = registry_cache_key(&config);
+    let registry = if let Some(registry) = load_cached_registry(&cache_key) {
+        log::debug!("registry loaded from cache");
+        registry
+    } else {
+        let mut registry = match Registry::builtin() {
+            Ok(registry) => registry,
+            Err(err) => {
+                log::error!("failed to load giallo registry: {err}");
+                eprintln!("failed to load giallo registry: {err}");
+                process::exit(1);
+            }
+        };
+        log::debug!("registry loaded successfully");
 
-    let config = Config::load();
-    log::debug!("config loaded: {:?}", config);
+        // Load custom grammars from config
+        if let Some(ref grammars_path) = config.grammars_path {
+            if let Err(err) = load_custom_grammars(&mut registry, grammars_path) {
+                log::error!("failed to load custom grammars: {err}");
+                eprintln!("warning: failed to load custom grammars: {err}");
+            }
+        }
 
-    // Load custom grammars from config
-    if let Some(ref grammars_path) = config.grammars_path {
-        if let Err(err) = load_custom_grammars(&mut registry, grammars_path) {
-            log::error!("failed to load custom grammars: {err}");
-            eprintln!("warning: failed to load custom grammars: {err}");
+        // Load custom themes from config
+        if let Some(ref themes_path) = config.themes_path {
+            if let Err(err) = load_custom_themes(&mut registry, themes_path) {
+                log::error!("failed to load custom themes: {err}");
+                eprintln!("warning: failed to load custom themes: {err}");
+            }
         }
-    }
 
-    // Load custom themes from config
-    if let Some(ref themes_path) = config.themes_path {
-        if let Err(err) = load_custom_themes(&mut registry, themes_path) {
-            log::error!("failed to load custom themes: {err}");
-            eprintln!("warning: failed to load custom themes: {err}");
+        // Fetch and load any grammars pinned in grammars.toml that aren't
+        // already on disk; an unchanged owner/repo@rev is a cache hit.
+        let manifest = GrammarManifest::load();
+        for (lang, source) in &manifest.grammars {
+            match fetch_grammar(&config, lang, source, false) {
+                Ok(paths) => {
+                    for path in &paths {
+                        match registry.add_grammar_from_path(path) {
+                            Ok(_) => {
+                                log::info!("fetched grammar: {} ({})", lang, path.display());
+                                if let Some(meta) = load_grammar_meta(path) {
+                                    add_grammar_aliases(&mut registry, &meta, path);
+                                }
+                            }
+                            Err(err) => {
+                                log::error!("failed to register fetched grammar {}: {}", lang, err)
+                            }
+                        }
+                    }
+                }
+                Err(err) => log::warn!("grammar fetch failed for lang={}: {}", lang, err),
+            }
         }
-    }
 
-    registry.link_grammars();
-    log::debug!("grammars linked");
+        registry.link_grammars();
+        log::debug!("grammars linked");
+
+        write_registry_cache(&cache_key, &registry);
+        registry
+    };
 
-    // Wrap registry in Arc for sharing across threads
-    let registry = Arc::new(registry);
-    log::debug!("registry wrapped in Arc for thread sharing");
+    // Wrap registry in a Mutex so a live `FETCH` can register a new grammar
+    // without tearing down the server, then in an Arc for thread sharing.
+    let registry = Arc::new(Mutex::new(registry));
+    log::debug!("registry wrapped in Arc<Mutex<_>> for thread sharing");
+
+    // Hot-reload custom grammars/themes as their files change, so editing a
+    // theme takes effect on the next highlight without restarting the
+    // server (a `RELOAD` FIFO command is also available for triggering this
+    // immediately rather than waiting on the poll/debounce window).
+    if config.grammars_path.is_some() || config.themes_path.is_some() {
+        let watch_registry = Arc::clone(&registry);
+        let watch_grammars_path = config.grammars_path.clone();
+        let watch_themes_path = config.themes_path.clone();
+        let watch_quit = resources.quit_flag();
+        thread::spawn(move || {
+            watch_custom_assets(watch_registry, watch_grammars_path, watch_themes_path, watch_quit);
+        });
+    }
 
     match mode {
         Mode::Stdio => {
@@ -1425,10 +4593,26 @@ fn main() {
             }
         }
         Mode::ListGrammars => {
-            list_grammars(&registry, &config);
+            list_grammars(&registry.lock().unwrap(), &config);
         }
         Mode::ListThemes => {
-            list_themes(&registry, &config);
+            list_themes(&registry.lock().unwrap(), &config);
+        }
+        Mode::LintTheme {
+            theme,
+            lang,
+            sample_path,
+        } => {
+            run_lint_theme(&registry.lock().unwrap(), &config, &theme, &lang, sample_path.as_deref());
+        }
+        Mode::ShowThemes { lang, sample_path, theme_name } => {
+            run_show_themes(&registry.lock().unwrap(), &config, &lang, sample_path.as_deref(), theme_name.as_deref());
+        }
+        Mode::Install { manifest } => {
+            run_install(&config, &manifest);
+        }
+        Mode::FetchGrammars { force } => {
+            run_fetch_grammars(&config, force);
         }
         Mode::KakouneRc => unreachable!(),
     }
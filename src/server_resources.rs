@@ -3,22 +3,188 @@
 //! Handles graceful shutdown via signal handling and ensures cleanup
 //! of temporary directories and resources via RAII Drop trait.
 
-use std::path::PathBuf;
+use std::collections::{HashMap, VecDeque};
+use std::path::{Path, PathBuf};
 use std::sync::atomic::{AtomicBool, Ordering};
-use std::sync::Arc;
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Instant;
+
+/// Runtime state of a buffer's highlight worker thread.
+#[derive(Debug, Clone)]
+pub enum WorkerState {
+    Idle,
+    Highlighting,
+    Dead { error: String },
+    /// The worker's thread has exited in response to `KILL` (or server
+    /// shutdown), as opposed to `Dead`'s unrecoverable-error exit. Distinct
+    /// from `Dead` only so `WORKERS` output doesn't describe an
+    /// intentionally torn-down buffer as an error; either way, the entry is
+    /// reaped from `ServerResources::workers` shortly after (see
+    /// `ServerResources::reap_finished_workers`), so this state is normally
+    /// only observable in the brief window between the thread setting it
+    /// and the next reap.
+    Killed,
+}
+
+/// Shared handles a buffer worker thread updates as it moves between idle
+/// and highlighting, and checks alongside the global quit flag so a single
+/// buffer can be torn down via `KILL` without stopping the whole server.
+#[derive(Debug, Clone)]
+pub struct WorkerHandles {
+    pub state: Arc<Mutex<WorkerState>>,
+    pub last_activity: Arc<Mutex<Instant>>,
+    pub cancel_flag: Arc<AtomicBool>,
+}
+
+impl WorkerHandles {
+    fn new() -> Self {
+        Self {
+            state: Arc::new(Mutex::new(WorkerState::Idle)),
+            last_activity: Arc::new(Mutex::new(Instant::now())),
+            cancel_flag: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// Mark the worker as actively highlighting and bump its activity clock.
+    pub fn mark_highlighting(&self) {
+        *self.state.lock().unwrap() = WorkerState::Highlighting;
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Mark the worker as idle (blocked on its FIFO) and bump its activity clock.
+    pub fn mark_idle(&self) {
+        *self.state.lock().unwrap() = WorkerState::Idle;
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Mark the worker as dead after an unrecoverable error.
+    pub fn mark_dead(&self, error: String) {
+        *self.state.lock().unwrap() = WorkerState::Dead { error };
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Mark the worker as having exited because it was cancelled (`KILL` or
+    /// server shutdown), not because it failed.
+    pub fn mark_killed(&self) {
+        *self.state.lock().unwrap() = WorkerState::Killed;
+        *self.last_activity.lock().unwrap() = Instant::now();
+    }
+
+    /// Whether this worker's cancel flag has been set via `KILL`.
+    pub fn is_cancelled(&self) -> bool {
+        self.cancel_flag.load(Ordering::Relaxed)
+    }
+}
+
+/// A snapshot of one worker, suitable for serializing back to the client in
+/// response to the `WORKERS` protocol verb.
+#[derive(Debug, Clone)]
+pub struct WorkerInfo {
+    pub buffer: String,
+    pub lang: String,
+    pub theme: String,
+    pub state: WorkerState,
+    pub idle_secs: f64,
+}
+
+struct WorkerEntry {
+    lang: Arc<Mutex<String>>,
+    theme: Arc<Mutex<String>>,
+    handles: WorkerHandles,
+    join_handle: Option<JoinHandle<()>>,
+}
+
+/// One file's precomputed highlight output, cached by `CRAWL` so a later
+/// INIT/H for the same file can skip highlighting entirely.
+#[derive(Debug, Clone)]
+pub struct CrawlEntry {
+    pub lang: String,
+    pub theme: String,
+    pub commands: String,
+}
+
+/// Least-recently-used cache of crawled files, bounded both by entry count
+/// and by total cached bytes so crawling a huge repo can't exhaust memory.
+/// Inserting past either cap evicts the least-recently-used entry first.
+pub struct CrawlCache {
+    max_files: usize,
+    max_bytes: usize,
+    total_bytes: usize,
+    order: VecDeque<PathBuf>,
+    entries: HashMap<PathBuf, CrawlEntry>,
+}
+
+impl CrawlCache {
+    fn new(max_files: usize, max_bytes: usize) -> Self {
+        Self {
+            max_files,
+            max_bytes,
+            total_bytes: 0,
+            order: VecDeque::new(),
+            entries: HashMap::new(),
+        }
+    }
+
+    /// Look up a cached entry, marking it most-recently-used on a hit.
+    pub fn get(&mut self, path: &Path) -> Option<CrawlEntry> {
+        let entry = self.entries.get(path).cloned()?;
+        if let Some(pos) = self.order.iter().position(|p| p == path) {
+            let touched = self.order.remove(pos).unwrap();
+            self.order.push_back(touched);
+        }
+        Some(entry)
+    }
+
+    /// Insert or replace a cached entry, evicting least-recently-used
+    /// entries until both the file-count and byte-budget caps are met.
+    pub fn insert(&mut self, path: PathBuf, entry: CrawlEntry) {
+        if let Some(old) = self.entries.remove(&path) {
+            self.total_bytes -= old.commands.len();
+            self.order.retain(|p| p != &path);
+        }
+
+        self.total_bytes += entry.commands.len();
+        self.order.push_back(path.clone());
+        self.entries.insert(path, entry);
+
+        while (self.entries.len() > self.max_files || self.total_bytes > self.max_bytes)
+            && !self.order.is_empty()
+        {
+            if let Some(oldest) = self.order.pop_front() {
+                if let Some(evicted) = self.entries.remove(&oldest) {
+                    self.total_bytes -= evicted.commands.len();
+                }
+            }
+        }
+    }
+
+    /// Number of files currently cached.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
 
 /// Manages server resources and handles graceful shutdown
-#[derive(Debug)]
 pub struct ServerResources {
     /// Base directory for temp files (FIFOs, etc.)
     base_dir: PathBuf,
     /// Atomic flag for quit signal
     quit_flag: Arc<AtomicBool>,
+    /// Buffer handler threads, keyed by buffer name
+    workers: Mutex<HashMap<String, WorkerEntry>>,
+    /// Pre-highlighted files from `CRAWL`, shared across all buffers.
+    pub crawl_cache: Mutex<CrawlCache>,
 }
 
 impl ServerResources {
-    /// Create new ServerResources with the given base directory
-    pub fn new(base_dir: PathBuf) -> Self {
+    /// Create new ServerResources with the given base directory and crawl
+    /// cache budget (see `CrawlConfig`).
+    pub fn new(base_dir: PathBuf, crawl_max_files: usize, crawl_max_bytes: usize) -> Self {
         log::debug!(
             "Creating ServerResources with base_dir: {}",
             base_dir.display()
@@ -26,6 +192,106 @@ impl ServerResources {
         Self {
             base_dir,
             quit_flag: Arc::new(AtomicBool::new(false)),
+            workers: Mutex::new(HashMap::new()),
+            crawl_cache: Mutex::new(CrawlCache::new(crawl_max_files, crawl_max_bytes)),
+        }
+    }
+
+    /// Register a newly spawned buffer worker, returning the shared handles
+    /// the worker thread should update as it processes highlight requests.
+    /// Call `attach_join_handle` once the thread has actually been spawned.
+    pub fn register_worker(
+        &self,
+        buffer: String,
+        lang: Arc<Mutex<String>>,
+        theme: Arc<Mutex<String>>,
+    ) -> WorkerHandles {
+        let handles = WorkerHandles::new();
+        self.workers.lock().unwrap().insert(
+            buffer,
+            WorkerEntry {
+                lang,
+                theme,
+                handles: handles.clone(),
+                join_handle: None,
+            },
+        );
+        handles
+    }
+
+    /// Attach the `JoinHandle` for a registered worker so `Drop` can join it.
+    pub fn attach_join_handle(&self, buffer: &str, join_handle: JoinHandle<()>) {
+        if let Some(entry) = self.workers.lock().unwrap().get_mut(buffer) {
+            entry.join_handle = Some(join_handle);
+        }
+    }
+
+    /// Drop the entry for every worker whose thread has actually finished
+    /// (checked via the non-blocking `JoinHandle::is_finished`, never by
+    /// joining a still-running thread). `KILL` only flips a cancel flag —
+    /// the buffer thread notices and exits on its own time — so without
+    /// this, a killed buffer's last `Idle`/`Highlighting`/`Killed` snapshot
+    /// would sit in `workers` forever, long after the thread that produced
+    /// it is gone. Called before every read of `workers` so `WORKERS`/`KILL`
+    /// never answer from a stale entry.
+    fn reap_finished_workers(&self) {
+        let mut workers = self.workers.lock().unwrap();
+        let finished: Vec<String> = workers
+            .iter()
+            .filter(|(_, entry)| {
+                entry
+                    .join_handle
+                    .as_ref()
+                    .is_some_and(|handle| handle.is_finished())
+            })
+            .map(|(buffer, _)| buffer.clone())
+            .collect();
+
+        for buffer in finished {
+            if let Some(entry) = workers.remove(&buffer) {
+                if let Some(join_handle) = entry.join_handle {
+                    if let Err(err) = join_handle.join() {
+                        log::warn!("worker thread for buffer={} panicked: {:?}", buffer, err);
+                    }
+                }
+            }
+        }
+    }
+
+    /// List all known workers for the `WORKERS` protocol verb.
+    pub fn list_workers(&self) -> Vec<WorkerInfo> {
+        self.reap_finished_workers();
+        self.workers
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(buffer, entry)| WorkerInfo {
+                buffer: buffer.clone(),
+                lang: entry.lang.lock().unwrap().clone(),
+                theme: entry.theme.lock().unwrap().clone(),
+                state: entry.handles.state.lock().unwrap().clone(),
+                idle_secs: entry
+                    .handles
+                    .last_activity
+                    .lock()
+                    .unwrap()
+                    .elapsed()
+                    .as_secs_f64(),
+            })
+            .collect()
+    }
+
+    /// Flip a worker's cancel flag so its buffer thread can tear itself down
+    /// without affecting the rest of the server. Returns `false` if no
+    /// worker is registered for that buffer.
+    pub fn kill_worker(&self, buffer: &str) -> bool {
+        self.reap_finished_workers();
+        match self.workers.lock().unwrap().get(buffer) {
+            Some(entry) => {
+                entry.handles.cancel_flag.store(true, Ordering::Relaxed);
+                true
+            }
+            None => false,
         }
     }
 
@@ -62,6 +328,19 @@ impl Drop for ServerResources {
     fn drop(&mut self) {
         log::info!("Cleaning up server resources");
 
+        // Set every worker's cancel flag and join its thread so none are
+        // left running past the server's own lifetime.
+        let mut workers = self.workers.lock().unwrap();
+        for (buffer, entry) in workers.drain() {
+            entry.handles.cancel_flag.store(true, Ordering::Relaxed);
+            if let Some(join_handle) = entry.join_handle {
+                if let Err(err) = join_handle.join() {
+                    log::warn!("worker thread for buffer={} panicked: {:?}", buffer, err);
+                }
+            }
+        }
+        drop(workers);
+
         // Remove temp directory and all contents (FIFOs, etc.)
         if self.base_dir.exists() {
             if let Err(e) = std::fs::remove_dir_all(&self.base_dir) {
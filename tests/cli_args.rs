@@ -0,0 +1,85 @@
+//! Black-box coverage of `parse_args_from`'s behavior (see its doc comment
+//! in `src/main.rs`, which was factored out precisely so the CLI surface
+//! could be exercised like this): missing flag values, unknown flags, the
+//! `--help`/`--version` early exits, and mode selection. `parse_args_from`
+//! itself is private to the binary crate, so these drive it the only way an
+//! external test can: spawning the real `giallo-kak` binary and asserting
+//! on its exit code and output, the same boundary `terraform_oneshot.rs`
+//! uses for `--oneshot`.
+
+use std::process::Command;
+
+fn run(args: &[&str]) -> (i32, String, String) {
+    let bin = env!("CARGO_BIN_EXE_giallo-kak");
+    let output = Command::new(bin)
+        .args(args)
+        .output()
+        .expect("failed to spawn giallo-kak");
+    (
+        output.status.code().unwrap_or(-1),
+        String::from_utf8_lossy(&output.stdout).into_owned(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
+
+#[test]
+fn help_flag_prints_usage_and_exits_zero() {
+    let (code, stdout, _stderr) = run(&["--help"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("USAGE:"));
+    assert!(stdout.contains("giallo-kak"));
+}
+
+#[test]
+fn short_help_flag_matches_long_form() {
+    let (code, stdout, _stderr) = run(&["-h"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("USAGE:"));
+}
+
+#[test]
+fn version_flag_prints_version_and_exits_zero() {
+    let (code, stdout, _stderr) = run(&["--version"]);
+    assert_eq!(code, 0);
+    assert!(stdout.contains("giallo-kak"));
+    assert!(stdout.contains(env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn unknown_flag_exits_nonzero_with_usage_on_stderr() {
+    let (code, _stdout, stderr) = run(&["--not-a-real-flag"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("unrecognized argument"));
+    assert!(stderr.contains("--not-a-real-flag"));
+    assert!(stderr.contains("USAGE:"));
+}
+
+#[test]
+fn flag_missing_its_value_exits_nonzero() {
+    let (code, _stdout, stderr) = run(&["--fifo"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("--fifo"));
+    assert!(stderr.contains("requires a value"));
+}
+
+#[test]
+fn install_without_manifest_argument_is_a_missing_value() {
+    let (code, _stdout, stderr) = run(&["install"]);
+    assert_eq!(code, 2);
+    assert!(stderr.contains("requires a value"));
+}
+
+#[test]
+fn init_selects_kakoune_rc_mode_and_prints_rc_snippet() {
+    let (code, stdout, _stderr) = run(&["init"]);
+    assert_eq!(code, 0);
+    assert!(!stdout.is_empty(), "init should print the rc snippet");
+}
+
+#[test]
+fn print_rc_alias_selects_the_same_mode_as_init() {
+    let (init_code, init_stdout, _) = run(&["init"]);
+    let (alias_code, alias_stdout, _) = run(&["--print-rc"]);
+    assert_eq!(init_code, alias_code);
+    assert_eq!(init_stdout, alias_stdout);
+}
@@ -4,61 +4,287 @@
 //! between the server and Kakoune editor.
 
 use std::fs;
-use std::io::{Read, Write};
+use std::io::{self, BufRead, BufReader, Read, Write};
 use std::path::{Path, PathBuf};
-use std::process::{Command, Stdio};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
 use std::thread;
-use std::time::{Duration, Instant};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tempfile::TempDir;
 
+/// Counter mixed into every session name so concurrent test binaries (and
+/// concurrent tests within one binary, under `cargo nextest`) never collide
+/// on the same Kakoune session, even when they start within the same
+/// process-id/timestamp tick.
+static SESSION_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Build a session name that's unique across processes and threads without
+/// pulling in a `rand` dependency: pid distinguishes concurrent `cargo test`
+/// binaries, the atomic counter distinguishes sessions within one binary,
+/// and the timestamp guards against a stale daemon from a prior run reusing
+/// the same pid.
+fn unique_session_name() -> String {
+    let counter = SESSION_COUNTER.fetch_add(1, Ordering::Relaxed);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    format!("giallo-test-{}-{}-{}", std::process::id(), counter, nanos)
+}
+
+/// A rendered atom's resolved face, as sent by Kakoune's `-ui json` client.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Face {
+    pub fg: String,
+    pub bg: String,
+    pub attributes: Vec<String>,
+}
+
+/// Where a `KakouneSession`'s Kakoune daemon actually runs. `Local` shells
+/// out directly on this machine, exactly as before. `Ssh` routes every
+/// `kak` invocation and file read/write through `ssh host -- ...` against a
+/// temp dir under `/tmp` on the remote host, so the same test suite can
+/// validate a giallo server binary already installed on a remote dev box or
+/// container — catching PATH/binary-resolution and socket-path assumptions
+/// a local-only harness can't.
+#[derive(Debug, Clone)]
+pub enum Transport {
+    Local,
+    Ssh { host: String },
+}
+
+impl Transport {
+    /// Build a transport from `GIALLO_E2E_SSH_HOST`, falling back to
+    /// `Local` when it's unset or empty. Tests that want remote coverage
+    /// opt in by constructing sessions with `Transport::from_env()` instead
+    /// of assuming `Local`.
+    pub fn from_env() -> Self {
+        match std::env::var("GIALLO_E2E_SSH_HOST") {
+            Ok(host) if !host.is_empty() => Transport::Ssh { host },
+            _ => Transport::Local,
+        }
+    }
+
+    /// Build a `Command` that runs `program` with `args` and `envs`, either
+    /// directly or wrapped in a single `ssh host '...'` remote command.
+    fn spawn(&self, program: &str, args: &[&str], envs: &[(&str, &str)]) -> Command {
+        match self {
+            Transport::Local => {
+                let mut cmd = Command::new(program);
+                cmd.args(args);
+                for (key, value) in envs {
+                    cmd.env(key, value);
+                }
+                cmd
+            }
+            Transport::Ssh { host } => {
+                let mut remote = String::new();
+                for (key, value) in envs {
+                    remote.push_str(&format!("{}={} ", key, shell_quote(value)));
+                }
+                remote.push_str(program);
+                for arg in args {
+                    remote.push(' ');
+                    remote.push_str(&shell_quote(arg));
+                }
+                let mut cmd = Command::new("ssh");
+                cmd.arg(host).arg(remote);
+                cmd
+            }
+        }
+    }
+
+    /// Write `contents` to `path` on whichever host this transport targets.
+    fn write_file(&self, path: &Path, contents: &str) -> io::Result<()> {
+        match self {
+            Transport::Local => fs::write(path, contents),
+            Transport::Ssh { host } => {
+                let remote = format!("cat > {}", shell_quote(&path.to_string_lossy()));
+                let mut child = Command::new("ssh")
+                    .arg(host)
+                    .arg(remote)
+                    .stdin(Stdio::piped())
+                    .spawn()?;
+                child
+                    .stdin
+                    .take()
+                    .expect("ssh child has no stdin")
+                    .write_all(contents.as_bytes())?;
+                child.wait()?;
+                Ok(())
+            }
+        }
+    }
+
+    /// Read the contents of `path` back from whichever host this transport
+    /// targets.
+    fn read_file(&self, path: &Path) -> io::Result<String> {
+        match self {
+            Transport::Local => fs::read_to_string(path),
+            Transport::Ssh { host } => {
+                let remote = format!("cat {}", shell_quote(&path.to_string_lossy()));
+                let output = Command::new("ssh").arg(host).arg(remote).output()?;
+                Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            }
+        }
+    }
+
+    fn remove_file(&self, path: &Path) {
+        match self {
+            Transport::Local => {
+                let _ = fs::remove_file(path);
+            }
+            Transport::Ssh { host } => {
+                let remote = format!("rm -f {}", shell_quote(&path.to_string_lossy()));
+                let _ = Command::new("ssh").arg(host).arg(remote).status();
+            }
+        }
+    }
+
+    fn file_exists(&self, path: &Path) -> bool {
+        match self {
+            Transport::Local => path.exists(),
+            Transport::Ssh { host } => {
+                let remote = format!("test -e {}", shell_quote(&path.to_string_lossy()));
+                Command::new("ssh")
+                    .arg(host)
+                    .arg(remote)
+                    .status()
+                    .map(|status| status.success())
+                    .unwrap_or(false)
+            }
+        }
+    }
+
+    fn remove_dir_all(&self, path: &Path) {
+        match self {
+            Transport::Local => {
+                let _ = fs::remove_dir_all(path);
+            }
+            Transport::Ssh { host } => {
+                let remote = format!("rm -rf {}", shell_quote(&path.to_string_lossy()));
+                let _ = Command::new("ssh").arg(host).arg(remote).status();
+            }
+        }
+    }
+}
+
+/// Quote `arg` as a single POSIX shell word so it survives being sent to a
+/// remote shell via `ssh host '<remote command>'`.
+fn shell_quote(arg: &str) -> String {
+    format!("'{}'", arg.replace('\'', "'\\''"))
+}
+
 /// Represents a Kakoune session for testing
 pub struct KakouneSession {
     session_name: String,
-    temp_dir: TempDir,
+    transport: Transport,
+    base_dir: PathBuf,
+    // Keeps the local temp dir alive for the session's lifetime; `None`
+    // when `transport` is `Ssh`, where `base_dir` instead lives on the
+    // remote host and is cleaned up by hand in `shutdown`/`Drop`.
+    _local_temp_dir: Option<TempDir>,
     kak_pid: Option<u32>,
     _giallo_bin: PathBuf, // Path to test binary (stored for debugging)
+    // One long-lived `kak -p` pipe, kept open for the life of the session
+    // instead of spawning a fresh process for every command.
+    pipe_stdin: Mutex<ChildStdin>,
+    pipe_child: Child,
 }
 
 impl KakouneSession {
-    /// Create a new Kakoune session with giallo.kak loaded
+    /// Create a new Kakoune session with giallo.kak loaded on the local
+    /// host.
     pub fn new() -> Self {
-        let temp_dir = TempDir::new().expect("failed to create temp dir");
-        let session_name = format!("giallo-test-{}", std::process::id());
+        Self::with_transport(Transport::Local)
+    }
+
+    /// Same as `new()`, but route the Kakoune daemon, its control pipe, and
+    /// every buffer/option file through `transport`. Pass
+    /// `Transport::from_env()` (or `Transport::Ssh { host }` directly) to
+    /// exercise a giallo server binary already installed on a remote
+    /// machine instead of the one built for this test run.
+    pub fn with_transport(transport: Transport) -> Self {
+        let session_name = unique_session_name();
+
+        let (base_dir, local_temp_dir) = match &transport {
+            Transport::Local => {
+                let temp_dir = TempDir::new().expect("failed to create temp dir");
+                let base_dir = temp_dir.path().to_path_buf();
+                (base_dir, Some(temp_dir))
+            }
+            Transport::Ssh { host } => {
+                let base_dir = PathBuf::from(format!("/tmp/{session_name}"));
+                let status = Command::new("ssh")
+                    .arg(host)
+                    .arg(format!("mkdir -p {}", shell_quote(&base_dir.to_string_lossy())))
+                    .status()
+                    .expect("failed to create remote temp dir over ssh");
+                assert!(
+                    status.success(),
+                    "mkdir -p {} on {} over ssh failed",
+                    base_dir.display(),
+                    host
+                );
+                (base_dir, None)
+            }
+        };
+
         let giallo_bin = PathBuf::from(env!("CARGO_BIN_EXE_giallo-kak"));
 
-        // Verify the test binary exists
-        assert!(
-            giallo_bin.exists(),
-            "giallo-kak test binary not found at: {:?}",
-            giallo_bin
-        );
+        // The local test binary only matters for `Local` sessions; a
+        // remote host is expected to already have giallo installed on its
+        // PATH, which is the whole point of exercising this transport.
+        let mut envs: Vec<(&str, String)> =
+            vec![("KAKOUNE_CONFIG_DIR", base_dir.to_string_lossy().into_owned())];
+        if matches!(transport, Transport::Local) {
+            assert!(
+                giallo_bin.exists(),
+                "giallo-kak test binary not found at: {:?}",
+                giallo_bin
+            );
+
+            let giallo_bin_dir = giallo_bin
+                .parent()
+                .expect("failed to get giallo-kak directory");
+            let path_separator = if cfg!(windows) { ";" } else { ":" };
+            let path_env = std::env::var_os("PATH").unwrap_or_default();
+            let mut new_path = std::ffi::OsString::from(giallo_bin_dir);
+            new_path.push(path_separator);
+            new_path.push(&path_env);
+            envs.push((
+                "PATH",
+                new_path.to_str().expect("PATH is not utf-8").to_string(),
+            ));
+        }
 
-        // Find rc/giallo.kak relative to project root
+        // Find rc/giallo.kak relative to project root and ship its
+        // contents to wherever the session's base dir actually lives, so
+        // `source` resolves on the host running Kakoune.
         let manifest_dir = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
         let giallo_rc = manifest_dir.join("rc").join("giallo.kak");
+        let giallo_rc_content =
+            fs::read_to_string(&giallo_rc).expect("failed to read rc/giallo.kak");
+        let remote_rc_path = base_dir.join("giallo.kak");
+        transport
+            .write_file(&remote_rc_path, &giallo_rc_content)
+            .expect("failed to ship rc/giallo.kak to session host");
+
+        let kakrc_path = base_dir.join("kakrc");
+        let kakrc_content = format!(
+            "source {}\n",
+            remote_rc_path.to_str().expect("invalid path")
+        );
+        transport
+            .write_file(&kakrc_path, &kakrc_content)
+            .expect("failed to write kakrc");
+
+        let envs_ref: Vec<(&str, &str)> = envs.iter().map(|(k, v)| (*k, v.as_str())).collect();
 
-        // Create minimal kakrc that sources giallo.kak
-        let kakrc_path = temp_dir.path().join("kakrc");
-        let kakrc_content = format!("source {}\n", giallo_rc.to_str().expect("invalid path"));
-        fs::write(&kakrc_path, kakrc_content).expect("failed to write kakrc");
-
-        // Get the directory containing the test giallo-kak binary
-        let giallo_bin_dir = giallo_bin
-            .parent()
-            .expect("failed to get giallo-kak directory");
-
-        // Prepare PATH with test binary directory first
-        let path_separator = if cfg!(windows) { ";" } else { ":" };
-        let path_env = std::env::var_os("PATH").unwrap_or_default();
-        let mut new_path = std::ffi::OsString::from(giallo_bin_dir);
-        new_path.push(path_separator);
-        new_path.push(&path_env);
-
-        // Spawn Kakoune in daemon mode with modified PATH
-        let mut child = Command::new("kak")
-            .args(&["-d", "-s", &session_name])
-            .env("KAKOUNE_CONFIG_DIR", temp_dir.path())
-            .env("PATH", &new_path)
+        // Spawn Kakoune in daemon mode.
+        let mut child = transport
+            .spawn("kak", &["-d", "-s", &session_name], &envs_ref)
             .stdout(Stdio::null())
             .stderr(Stdio::null())
             .spawn()
@@ -69,11 +295,27 @@ impl KakouneSession {
         // Wait a moment for session to be ready
         thread::sleep(Duration::from_millis(200));
 
+        // Open one persistent `kak -p` pipe for the life of the session;
+        // `-p` reads commands from stdin until it's closed, so writing to
+        // this single child replaces spawning a process per command.
+        let mut pipe_child = transport
+            .spawn("kak", &["-p", &session_name], &[])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to spawn persistent kak -p pipe");
+        let pipe_stdin = pipe_child.stdin.take().expect("failed to get pipe stdin");
+
         let session = Self {
             session_name,
-            temp_dir,
+            transport,
+            base_dir,
+            _local_temp_dir: local_temp_dir,
             kak_pid: Some(pid),
             _giallo_bin: giallo_bin,
+            pipe_stdin: Mutex::new(pipe_stdin),
+            pipe_child,
         };
 
         // Verify session is alive
@@ -84,8 +326,9 @@ impl KakouneSession {
 
     /// Verify the Kakoune session is still running
     fn verify_session_alive(&self) {
-        let output = Command::new("kak")
-            .args(&["-l"])
+        let output = self
+            .transport
+            .spawn("kak", &["-l"], &[])
             .output()
             .expect("failed to list kak sessions");
 
@@ -98,51 +341,55 @@ impl KakouneSession {
         );
     }
 
-    /// Send a command to the Kakoune session
+    /// Send a command to the Kakoune session over the persistent pipe
     pub fn send_command(&self, command: &str) {
-        let output = Command::new("kak")
-            .args(&["-p", &self.session_name])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .output()
-            .expect("failed to run kak -p");
-
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            panic!("kak -p failed: {}", stderr);
-        }
+        self.write_to_pipe(command);
     }
 
-    /// Send a command via echo to kak -p
+    /// Send several commands to the Kakoune session over the persistent pipe
     pub fn send_commands(&self, commands: &[&str]) {
-        let script = commands.join("\n");
-        let mut child = Command::new("kak")
-            .args(&["-p", &self.session_name])
-            .stdin(Stdio::piped())
-            .stdout(Stdio::piped())
-            .stderr(Stdio::piped())
-            .spawn()
-            .expect("failed to spawn kak -p");
+        self.write_to_pipe(&commands.join("\n"));
+    }
 
-        {
-            let stdin = child.stdin.as_mut().expect("failed to get stdin");
-            stdin
-                .write_all(script.as_bytes())
-                .expect("failed to write to kak");
-        }
+    /// Write a script to the persistent `kak -p` pipe and flush it.
+    fn write_to_pipe(&self, script: &str) {
+        let mut stdin = self.pipe_stdin.lock().unwrap();
+        stdin
+            .write_all(script.as_bytes())
+            .expect("failed to write to kak pipe");
+        stdin
+            .write_all(b"\n")
+            .expect("failed to write to kak pipe");
+        stdin.flush().expect("failed to flush kak pipe");
+    }
 
-        let output = child.wait_with_output().expect("failed to wait for kak");
-        if !output.status.success() {
-            let stderr = String::from_utf8_lossy(&output.stderr);
-            panic!("kak -p failed: {}", stderr);
+    /// Send a command, then block until `predicate` returns true or
+    /// `timeout_ms` elapses, polling it at a short fixed interval instead of
+    /// sleeping for the whole timeout regardless of how quickly Kakoune
+    /// actually reacts. Returns whether the predicate was satisfied in time.
+    pub fn send_and_wait<F>(&self, command: &str, mut predicate: F, timeout_ms: u64) -> bool
+    where
+        F: FnMut() -> bool,
+    {
+        self.send_command(command);
+        let deadline = Instant::now() + Duration::from_millis(timeout_ms);
+        loop {
+            if predicate() {
+                return true;
+            }
+            if Instant::now() >= deadline {
+                return false;
+            }
+            thread::sleep(Duration::from_millis(5));
         }
     }
 
     /// Create a buffer with content
     pub fn create_buffer(&self, name: &str, content: &str) -> PathBuf {
-        let buffer_path = self.temp_dir.path().join(name);
-        fs::write(&buffer_path, content).expect("failed to write buffer content");
+        let buffer_path = self.base_dir.join(name);
+        self.transport
+            .write_file(&buffer_path, content)
+            .expect("failed to write buffer content");
 
         // Open the file in Kakoune
         self.send_commands(&[
@@ -153,26 +400,25 @@ impl KakouneSession {
         buffer_path
     }
 
-    /// Get an option value from a buffer
+    /// Get an option value from a buffer, written out via `echo -to-file` and
+    /// read back as soon as Kakoune has produced it rather than after a
+    /// fixed sleep.
     pub fn get_buffer_option(&self, buffer: &str, option: &str) -> String {
         let output_file = self
-            .temp_dir
-            .path()
+            .base_dir
             .join(format!("option_{}_{}", buffer, option));
+        self.transport.remove_file(&output_file);
 
-        self.send_commands(&[
-            &format!("buffer {}", buffer),
-            &format!(
-                "echo -to-file {} %opt{{{}}}",
-                output_file.to_str().unwrap(),
-                option
-            ),
-        ]);
-
-        // Give Kakoune time to write
-        thread::sleep(Duration::from_millis(100));
+        let command = format!(
+            "buffer {}\necho -to-file {} %opt{{{}}}",
+            buffer,
+            output_file.to_str().unwrap(),
+            option
+        );
+        self.send_and_wait(&command, || self.transport.file_exists(&output_file), 500);
 
-        fs::read_to_string(&output_file)
+        self.transport
+            .read_file(&output_file)
             .unwrap_or_default()
             .trim()
             .to_string()
@@ -184,7 +430,8 @@ impl KakouneSession {
         !ranges.is_empty() && ranges != ""
     }
 
-    /// Wait for highlighting to appear with timeout
+    /// Wait for highlighting to appear with timeout, polling rather than
+    /// blocking for the whole timeout once it's already there.
     pub fn wait_for_highlighting(&self, buffer: &str, timeout_ms: u64) -> bool {
         let start = Instant::now();
         let timeout = Duration::from_millis(timeout_ms);
@@ -193,16 +440,61 @@ impl KakouneSession {
             if self.has_highlighting(buffer) {
                 return true;
             }
-            thread::sleep(Duration::from_millis(50));
+            thread::sleep(Duration::from_millis(20));
         }
 
         false
     }
 
+    /// Attach a JSON-UI client (`kak -c <session> -ui json`) and capture the
+    /// most recent `draw` notification's grid of rendered atoms for
+    /// `buffer`, reconstructing each screen line's `(text, Face)` runs. This
+    /// verifies Kakoune actually *painted* the faces the server requested,
+    /// rather than just that `giallo_hl_ranges` was set.
+    pub fn capture_draw(&self, buffer: &str) -> Vec<Vec<(String, Face)>> {
+        self.send_commands(&[&format!("buffer {}", buffer)]);
+
+        let mut child = self
+            .transport
+            .spawn("kak", &["-c", &self.session_name, "-ui", "json"], &[])
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("failed to spawn kak -ui json client");
+
+        let stdout = child.stdout.take().expect("failed to get json client stdout");
+        let mut reader = BufReader::new(stdout);
+
+        let mut grid: Vec<Vec<(String, Face)>> = Vec::new();
+        let deadline = Instant::now() + Duration::from_secs(2);
+        let mut line = String::new();
+
+        while Instant::now() < deadline {
+            line.clear();
+            match reader.read_line(&mut line) {
+                Ok(0) => break,
+                Ok(_) => {
+                    if let Some(lines) = parse_draw_notification(&line) {
+                        grid = lines;
+                    }
+                }
+                Err(_) => break,
+            }
+        }
+
+        let _ = child.kill();
+        let _ = child.wait();
+
+        grid
+    }
+
     /// Edit buffer content
     pub fn edit_buffer(&self, buffer: &str, new_content: &str) {
-        let buffer_path = self.temp_dir.path().join(buffer);
-        fs::write(&buffer_path, new_content).expect("failed to write new content");
+        let buffer_path = self.base_dir.join(buffer);
+        self.transport
+            .write_file(&buffer_path, new_content)
+            .expect("failed to write new content");
 
         self.send_commands(&[
             &format!("buffer {}", buffer),
@@ -217,6 +509,37 @@ impl KakouneSession {
         if let Some(pid) = self.kak_pid.take() {
             let _ = Command::new("kill").arg(pid.to_string()).output();
         }
+        let _ = self.pipe_child.kill();
+        let _ = self.pipe_child.wait();
+        if matches!(self.transport, Transport::Ssh { .. }) {
+            self.transport.remove_dir_all(&self.base_dir);
+        }
+    }
+
+    /// Opt-in access to a single warm Kakoune daemon and giallo server
+    /// shared by every test case that asks for it, instead of each case
+    /// paying its own `kak -d` startup cost. Call `reset()` between cases
+    /// to clear out whatever buffers/state the previous case left behind.
+    /// Tests that need a pristine session of their own should keep using
+    /// `KakouneSession::new()`.
+    pub fn shared() -> &'static KakouneSession {
+        static SHARED: OnceLock<KakouneSession> = OnceLock::new();
+        SHARED.get_or_init(KakouneSession::new)
+    }
+
+    /// Close every buffer a prior test case left open on a shared session,
+    /// so the next case starts from a clean slate without restarting
+    /// Kakoune. Safe to call even if only the initial scratch buffer
+    /// remains.
+    pub fn reset(&self) {
+        self.send_command(
+            "evaluate-commands -no-hooks %sh{ \
+                for buf in $kak_buflist; do \
+                    printf 'try %%{ delete-buffer! %s }\\n' \"$buf\"; \
+                done \
+            }",
+        );
+        thread::sleep(Duration::from_millis(20));
     }
 }
 
@@ -225,6 +548,69 @@ impl Drop for KakouneSession {
         if let Some(pid) = self.kak_pid {
             let _ = Command::new("kill").arg(pid.to_string()).output();
         }
+        let _ = self.pipe_child.kill();
+        let _ = self.pipe_child.wait();
+        if matches!(self.transport, Transport::Ssh { .. }) {
+            self.transport.remove_dir_all(&self.base_dir);
+        }
+    }
+}
+
+/// Parse one line of Kakoune's `-ui json` stream, returning the reconstructed
+/// `(text, Face)` grid if the line is a `draw` notification. `draw`'s first
+/// param is an array of lines, each line an array of atoms shaped like
+/// `{"face": {"fg", "bg", "attributes"}, "contents": "..."}`.
+fn parse_draw_notification(line: &str) -> Option<Vec<Vec<(String, Face)>>> {
+    let value: serde_json::Value = serde_json::from_str(line.trim()).ok()?;
+    if value.get("method")?.as_str()? != "draw" {
+        return None;
+    }
+
+    let raw_lines = value.get("params")?.as_array()?.first()?.as_array()?;
+    Some(
+        raw_lines
+            .iter()
+            .map(|raw_line| {
+                raw_line
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .filter_map(parse_atom)
+                    .collect()
+            })
+            .collect(),
+    )
+}
+
+/// Parse one atom object into its text contents and resolved face.
+fn parse_atom(atom: &serde_json::Value) -> Option<(String, Face)> {
+    let contents = atom.get("contents")?.as_str()?.to_string();
+    let face = atom.get("face").map(parse_face).unwrap_or_default();
+    Some((contents, face))
+}
+
+fn parse_face(face: &serde_json::Value) -> Face {
+    Face {
+        fg: face
+            .get("fg")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        bg: face
+            .get("bg")
+            .and_then(|v| v.as_str())
+            .unwrap_or_default()
+            .to_string(),
+        attributes: face
+            .get("attributes")
+            .and_then(|v| v.as_array())
+            .map(|attrs| {
+                attrs
+                    .iter()
+                    .filter_map(|a| a.as_str().map(String::from))
+                    .collect()
+            })
+            .unwrap_or_default(),
     }
 }
 
@@ -270,6 +656,32 @@ fn e2e_session_creation() {
     drop(session);
 }
 
+/// Runs the same highlighting smoke test as `e2e_enable_highlighting`, but
+/// against `GIALLO_E2E_SSH_HOST` over SSH instead of the local host, to
+/// validate a giallo server binary already installed on a remote dev box or
+/// container. Skipped unless that env var is set, since CI and most dev
+/// machines don't have a remote target configured.
+#[test]
+fn e2e_remote_enable_highlighting_over_ssh() {
+    if std::env::var("GIALLO_E2E_SSH_HOST")
+        .map(|host| host.is_empty())
+        .unwrap_or(true)
+    {
+        println!("Skipping remote E2E test: GIALLO_E2E_SSH_HOST not set");
+        return;
+    }
+
+    let session = KakouneSession::with_transport(Transport::from_env());
+    let code = r#"fn main() { println!("Hello"); }"#;
+
+    session.create_buffer("test.rs", code);
+    session.send_command("giallo-enable");
+    assert!(
+        session.wait_for_highlighting("test.rs", 5000),
+        "Remote buffer should have highlighting within 5 seconds"
+    );
+}
+
 #[test]
 fn e2e_enable_highlighting() {
     skip_if_no_kakoune();
@@ -451,3 +863,4 @@ fn e2e_server_reconnect() {
         "Should recover after server restart (may need manual re-init)"
     );
 }
+
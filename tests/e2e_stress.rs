@@ -5,7 +5,7 @@
 
 mod resource_monitor;
 
-use resource_monitor::ResourceMonitor;
+use resource_monitor::{MultiProcessMonitor, ResourceMonitor, ResourceReport, ResourceThresholds};
 use std::fs;
 use std::io::Write;
 use std::path::PathBuf;
@@ -106,6 +106,32 @@ impl StressTestSession {
         buffer_names
     }
 
+    /// As `create_multiple_buffers`, but creates the buffers in a
+    /// `seed`-shuffled index order instead of `0..count`. Since
+    /// `enable_all_buffers`/`rehighlight_all` iterate `self.buffers` (which
+    /// this stores in the same shuffled order), enabling and rehighlighting
+    /// follow the shuffle too without needing separate shuffled variants.
+    pub fn create_multiple_buffers_shuffled(
+        &mut self,
+        count: usize,
+        pattern: &str,
+        seed: u64,
+    ) -> Vec<String> {
+        let mut order: Vec<usize> = (0..count).collect();
+        shuffle_seeded(&mut order, seed);
+
+        let mut buffer_names = Vec::new();
+        for i in order {
+            let name = format!("{}_{:03}.rs", pattern, i);
+            let code = generate_test_code(i);
+            self.create_buffer(&name, &code);
+            buffer_names.push(name);
+        }
+
+        self.buffers.extend(buffer_names.clone());
+        buffer_names
+    }
+
     /// Create a single buffer
     pub fn create_buffer(&self, name: &str, content: &str) -> PathBuf {
         let buffer_path = self.temp_dir.path().join(name);
@@ -327,6 +353,88 @@ fn generate_test_code(index: usize) -> String {
     )
 }
 
+/// Deterministic xorshift64 PRNG used only to seed-shuffle the order
+/// buffers are created/enabled/typed into/rehighlighted in, so ordering-
+/// dependent races in concurrent highlighting get exercised reproducibly
+/// rather than always in the same index order. No `rand` dependency exists
+/// anywhere in this crate.
+struct Xorshift64(u64);
+
+impl Xorshift64 {
+    fn new(seed: u64) -> Self {
+        Xorshift64(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+/// Fisher-Yates shuffle of `items`, seeded so a run can be replayed exactly
+/// from the seed printed at the start of a stress test.
+fn shuffle_seeded<T>(items: &mut [T], seed: u64) {
+    let mut rng = Xorshift64::new(seed);
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() as usize) % (i + 1);
+        items.swap(i, j);
+    }
+}
+
+/// Seed for this run's buffer shuffling: `GIALLO_STRESS_SEED` if set,
+/// otherwise a seed derived from the system clock. Either way the seed is
+/// meant to be printed by the caller so a flaky run can be replayed exactly
+/// via `GIALLO_STRESS_SEED=<seed>`.
+fn stress_seed() -> u64 {
+    std::env::var("GIALLO_STRESS_SEED")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or_else(|| {
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos() as u64)
+                .unwrap_or(1)
+        })
+}
+
+/// Whether `scenario` should run, per `GIALLO_STRESS_FILTER` (a substring
+/// match against the scenario name, mirroring a test-harness
+/// `--shuffle-seed`/filter interface so a single scenario can be selected
+/// without recompiling). Unset means every scenario runs.
+fn scenario_enabled(scenario: &str) -> bool {
+    std::env::var("GIALLO_STRESS_FILTER")
+        .map(|filter| scenario.contains(filter.as_str()))
+        .unwrap_or(true)
+}
+
+/// If `GIALLO_STRESS_REPORT_DIR` is set, write `report` as a JSON, JUnit,
+/// and CSV artifact into that directory (`<scenario>.{json,xml,csv}`), so CI
+/// can track stress-test memory/throughput regressions over time. No-op
+/// otherwise, so a plain `cargo test` run is unaffected.
+fn export_report(scenario: &str, report: &ResourceReport, thresholds: &ResourceThresholds) {
+    let Ok(dir) = std::env::var("GIALLO_STRESS_REPORT_DIR") else {
+        return;
+    };
+    let dir = PathBuf::from(dir);
+    fs::create_dir_all(&dir).expect("failed to create stress report dir");
+    fs::write(
+        dir.join(format!("{scenario}.json")),
+        report.to_json(scenario, thresholds),
+    )
+    .expect("failed to write JSON stress report");
+    fs::write(
+        dir.join(format!("{scenario}.xml")),
+        report.to_junit(scenario, thresholds),
+    )
+    .expect("failed to write JUnit stress report");
+    fs::write(dir.join(format!("{scenario}.csv")), report.to_csv())
+        .expect("failed to write CSV stress report");
+}
+
 fn skip_if_no_kakoune() {
     if Command::new("kak").arg("-version").output().is_err() {
         println!("Skipping stress test: Kakoune not installed");
@@ -339,12 +447,19 @@ fn skip_if_no_kakoune() {
 #[test]
 fn stress_many_buffers() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_many_buffers") {
+        println!("Skipping stress_many_buffers (GIALLO_STRESS_FILTER set)");
+        return;
+    }
+
+    let seed = stress_seed();
+    println!("stress_many_buffers: shuffle seed = {seed} (replay with GIALLO_STRESS_SEED={seed})");
 
     let mut session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
 
     println!("Creating 20 buffers...");
-    let buffers = session.create_multiple_buffers(20, "stress");
+    let buffers = session.create_multiple_buffers_shuffled(20, "stress", seed);
     monitor.sample();
 
     println!("Enabling giallo on all buffers...");
@@ -362,11 +477,24 @@ fn stress_many_buffers() {
     let report = monitor.report();
     report.print_report();
 
-    // Memory should not exceed 200MB for 20 buffers
+    // Also snapshot the whole session (kak server, its `kak -p` clients,
+    // and the highlighter) without having to track any PID ourselves.
+    let mut session_monitor = MultiProcessMonitor::for_session(&session.session_name);
+    session_monitor.sample_all();
+    session_monitor.combined_report().print_report();
+
+    // p95 memory should not exceed 200MB for 20 buffers; using the 95th
+    // percentile instead of the absolute max avoids flaking on a single
+    // transient sampling spike.
+    let thresholds = ResourceThresholds {
+        max_p95_memory_mb: Some(200.0),
+        ..Default::default()
+    };
+    export_report("stress_many_buffers", &report, &thresholds);
     assert!(
-        report.max_memory_mb < 200.0,
-        "Memory usage too high: {:.2}MB for 20 buffers",
-        report.max_memory_mb
+        report.assert_within(&thresholds).is_ok(),
+        "resource thresholds breached: {:?}",
+        report.assert_within(&thresholds).err().unwrap_or_default()
     );
 
     println!("âœ“ Successfully managed {} buffers", buffers.len());
@@ -375,6 +503,10 @@ fn stress_many_buffers() {
 #[test]
 fn stress_rapid_editing() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_rapid_editing") {
+        println!("Skipping stress_rapid_editing (GIALLO_STRESS_FILTER set)");
+        return;
+    }
 
     let session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
@@ -400,6 +532,7 @@ fn stress_rapid_editing() {
     let report = monitor.report();
 
     report.print_report();
+    export_report("stress_rapid_editing", &report, &ResourceThresholds::default());
     println!("Total time for 100 edits: {:.2}s", elapsed.as_secs_f64());
 
     // Should complete in under 10 seconds
@@ -422,6 +555,10 @@ fn stress_rapid_editing() {
 #[test]
 fn stress_continuous_updates() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_continuous_updates") {
+        println!("Skipping stress_continuous_updates (GIALLO_STRESS_FILTER set)");
+        return;
+    }
 
     let session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
@@ -450,6 +587,14 @@ fn stress_continuous_updates() {
 
     let report = monitor.report();
     report.print_report();
+    export_report(
+        "stress_continuous_updates",
+        &report,
+        &ResourceThresholds {
+            max_memory_growth_percent: Some(30.0),
+            ..Default::default()
+        },
+    );
 
     println!("Total updates: {}", update_count);
     println!("Updates per second: {:.1}", update_count as f64 / 30.0);
@@ -465,6 +610,10 @@ fn stress_continuous_updates() {
 #[test]
 fn stress_memory_stability() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_memory_stability") {
+        println!("Skipping stress_memory_stability (GIALLO_STRESS_FILTER set)");
+        return;
+    }
 
     let session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
@@ -485,6 +634,15 @@ fn stress_memory_stability() {
 
     let report = monitor.report();
     report.print_report();
+    export_report(
+        "stress_memory_stability",
+        &report,
+        &ResourceThresholds {
+            max_memory_growth_percent: Some(50.0),
+            max_avg_cpu: Some(20.0),
+            ..Default::default()
+        },
+    );
 
     // Memory should be relatively stable (no leaks)
     // Allow 50% growth for caching and normal operation
@@ -505,12 +663,21 @@ fn stress_memory_stability() {
 #[test]
 fn stress_concurrent_typing() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_concurrent_typing") {
+        println!("Skipping stress_concurrent_typing (GIALLO_STRESS_FILTER set)");
+        return;
+    }
+
+    let seed = stress_seed();
+    println!("stress_concurrent_typing: shuffle seed = {seed} (replay with GIALLO_STRESS_SEED={seed})");
 
     let mut session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
 
-    // Create 5 buffers
-    let buffers = session.create_multiple_buffers(5, "concurrent");
+    // Create 5 buffers, in shuffled order so enabling/typing/rehighlighting
+    // below (which both iterate this same order) don't always race the same
+    // buffer first.
+    let buffers = session.create_multiple_buffers_shuffled(5, "concurrent", seed);
     session.enable_all_buffers();
 
     println!("Simulating typing in 5 buffers simultaneously...");
@@ -523,8 +690,13 @@ fn stress_concurrent_typing() {
         }
 
         if i % 3 == 0 {
+            // Clip the resource samples around this rehighlight pass, so a
+            // memory/CPU spike in the report can be traced back to the
+            // specific batch of buffer updates that triggered it.
+            monitor.begin_clip(&format!("rehighlight_iteration_{i}"));
             session.rehighlight_all();
             monitor.sample();
+            monitor.end_clip();
         }
 
         thread::sleep(Duration::from_millis(100));
@@ -534,6 +706,14 @@ fn stress_concurrent_typing() {
     let report = monitor.report();
 
     report.print_report();
+    export_report(
+        "stress_concurrent_typing",
+        &report,
+        &ResourceThresholds {
+            max_p95_memory_mb: Some(100.0),
+            ..Default::default()
+        },
+    );
     println!(
         "Completed {} iterations across 5 buffers in {:.2}s",
         iterations,
@@ -546,17 +726,22 @@ fn stress_concurrent_typing() {
         "Not all buffers have highlighting after concurrent edits"
     );
 
-    // Memory should stay reasonable
+    // p95 memory should stay reasonable; the max alone is noisy under
+    // concurrent load since any single GC-ish spike would fail the test.
     assert!(
-        report.max_memory_mb < 100.0,
-        "Memory too high with concurrent buffers: {:.2}MB",
-        report.max_memory_mb
+        report.p95_memory_mb < 100.0,
+        "p95 memory too high with concurrent buffers: {:.2}MB",
+        report.p95_memory_mb
     );
 }
 
 #[test]
 fn stress_large_file_editing() {
     skip_if_no_kakoune();
+    if !scenario_enabled("stress_large_file_editing") {
+        println!("Skipping stress_large_file_editing (GIALLO_STRESS_FILTER set)");
+        return;
+    }
 
     let session = StressTestSession::new();
     let mut monitor = ResourceMonitor::for_current_process();
@@ -604,6 +789,14 @@ fn stress_large_file_editing() {
 
     let report = monitor.report();
     report.print_report();
+    export_report(
+        "stress_large_file_editing",
+        &report,
+        &ResourceThresholds {
+            max_p95_memory_mb: Some(150.0),
+            ..Default::default()
+        },
+    );
 
     // Large file should highlight within 20 seconds (very conservative for CI)
     assert!(
@@ -612,10 +805,10 @@ fn stress_large_file_editing() {
         initial_highlight_time
     );
 
-    // Memory for large file should be under 150MB
+    // p95 memory for large file should be under 150MB
     assert!(
-        report.max_memory_mb < 150.0,
+        report.p95_memory_mb < 150.0,
         "Large file memory usage too high: {:.2}MB",
-        report.max_memory_mb
+        report.p95_memory_mb
     );
 }
@@ -27,12 +27,47 @@ fn write_config(config_dir: &Path, theme: &str) {
     fs::write(&config_path, contents).expect("failed to write config");
 }
 
-fn run_oneshot_highlight(lang: &str, theme: &str, code: &str) -> String {
+/// Same as `write_config`, but with an `[rainbow]` section appended so tests
+/// can opt into rainbow delimiter highlighting without touching every other
+/// `write_config` call site.
+fn write_config_with_rainbow(config_dir: &Path, theme: &str) {
+    let cfg_dir = config_dir.join("giallo.kak");
+    fs::create_dir_all(&cfg_dir).expect("failed to create config dir");
+    let config_path = cfg_dir.join("config.toml");
+    let contents = format!("theme = \"{}\"\n\n[rainbow]\nenabled = true\n", theme);
+    fs::write(&config_path, contents).expect("failed to write config");
+}
+
+/// Same as `write_config`, but with `[highlight] strings = false` appended
+/// so tests can assert string tokens fall back to the `default` face.
+fn write_config_with_strings_disabled(config_dir: &Path, theme: &str) {
+    let cfg_dir = config_dir.join("giallo.kak");
+    fs::create_dir_all(&cfg_dir).expect("failed to create config dir");
+    let config_path = cfg_dir.join("config.toml");
+    let contents = format!("theme = \"{}\"\n\n[highlight]\nstrings = false\n", theme);
+    fs::write(&config_path, contents).expect("failed to write config");
+}
+
+/// Same as `write_config`, but with a `[semantic]` section appended so tests
+/// can opt into semantic modifier highlighting without touching every other
+/// `write_config` call site.
+fn write_config_with_semantic(config_dir: &Path, theme: &str) {
+    let cfg_dir = config_dir.join("giallo.kak");
+    fs::create_dir_all(&cfg_dir).expect("failed to create config dir");
+    let config_path = cfg_dir.join("config.toml");
+    let contents = format!("theme = \"{}\"\n\n[semantic]\nenabled = true\n", theme);
+    fs::write(&config_path, contents).expect("failed to write config");
+}
+
+/// Run `giallo-kak --oneshot`, piping a `H {lang} {theme} {len} {format}`
+/// header plus `code`, and return stdout. `run_oneshot_highlight` calls it
+/// with the default `kakoune` serialization.
+fn run_oneshot_highlight_format(lang: &str, theme: &str, code: &str, format: &str) -> String {
     let config_home = make_temp_dir("giallo-kak-test-config");
     write_config(&config_home, theme);
 
     let payload = code.as_bytes();
-    let header = format!("H {} {} {}\n", lang, theme, payload.len());
+    let header = format!("H {} {} {} {}\n", lang, theme, payload.len(), format);
 
     let bin = env!("CARGO_BIN_EXE_giallo-kak");
     let mut child = Command::new(bin)
@@ -60,6 +95,67 @@ fn run_oneshot_highlight(lang: &str, theme: &str, code: &str) -> String {
     String::from_utf8_lossy(&output.stdout).to_string()
 }
 
+fn run_oneshot_highlight(lang: &str, theme: &str, code: &str) -> String {
+    run_oneshot_highlight_format(lang, theme, code, "kakoune")
+}
+
+/// Like `run_oneshot_highlight`, but with `[rainbow] enabled = true` set in
+/// the `$XDG_CONFIG_HOME` config file, so callers can assert on the `giallo_rainbow_*`
+/// faces/ranges it adds.
+fn run_oneshot_highlight_rainbow(lang: &str, theme: &str, code: &str) -> String {
+    let config_home = make_temp_dir("giallo-kak-test-config-rainbow");
+    write_config_with_rainbow(&config_home, theme);
+    run_oneshot_with_config_home(lang, theme, code, &config_home)
+}
+
+/// Like `run_oneshot_highlight`, but with `[highlight] strings = false` set
+/// in the `$XDG_CONFIG_HOME` config file, so callers can assert string tokens lose their
+/// distinct face.
+fn run_oneshot_highlight_strings_disabled(lang: &str, theme: &str, code: &str) -> String {
+    let config_home = make_temp_dir("giallo-kak-test-config-no-strings");
+    write_config_with_strings_disabled(&config_home, theme);
+    run_oneshot_with_config_home(lang, theme, code, &config_home)
+}
+
+/// Like `run_oneshot_highlight`, but with `[semantic] enabled = true` set in
+/// the `$XDG_CONFIG_HOME` config file, so callers can assert on the `giallo_variable_mutable`
+/// /`giallo_function_declaration`/`giallo_keyword_unsafe` faces it adds.
+fn run_oneshot_highlight_semantic(lang: &str, theme: &str, code: &str) -> String {
+    let config_home = make_temp_dir("giallo-kak-test-config-semantic");
+    write_config_with_semantic(&config_home, theme);
+    run_oneshot_with_config_home(lang, theme, code, &config_home)
+}
+
+fn run_oneshot_with_config_home(lang: &str, theme: &str, code: &str, config_home: &Path) -> String {
+    let payload = code.as_bytes();
+    let header = format!("H {} {} {}\n", lang, theme, payload.len());
+
+    let bin = env!("CARGO_BIN_EXE_giallo-kak");
+    let mut child = Command::new(bin)
+        .arg("--oneshot")
+        .env("XDG_CONFIG_HOME", config_home)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("failed to spawn giallo-kak");
+
+    {
+        let stdin = child.stdin.as_mut().expect("failed to open stdin");
+        stdin
+            .write_all(header.as_bytes())
+            .expect("failed to write header");
+        stdin.write_all(payload).expect("failed to write payload");
+    }
+
+    let output = child
+        .wait_with_output()
+        .expect("failed to read giallo-kak output");
+
+    assert!(output.status.success(), "giallo-kak failed");
+
+    String::from_utf8_lossy(&output.stdout).to_string()
+}
+
 fn assert_valid_highlighting(output: &str, fixture_name: &str) {
     // The output should contain face definitions
     assert!(
@@ -103,6 +199,28 @@ fn assert_valid_highlighting(output: &str, fixture_name: &str) {
     }
 }
 
+/// Like `count_highlights`, but only counts ranges that carry a face other
+/// than `default` — used to assert that a `[highlight]` toggle actually
+/// dropped a category's faces rather than just leaving the range count
+/// unchanged (disabling a category still emits a range, just with the
+/// `default` face).
+fn count_non_default_highlights(output: &str) -> usize {
+    let ranges_line = output
+        .lines()
+        .find(|line| line.starts_with("set-option buffer giallo_hl_ranges"))
+        .expect("should have ranges line");
+
+    let parts: Vec<&str> = ranges_line.split_whitespace().collect();
+    if parts.len() <= 4 {
+        return 0;
+    }
+
+    parts[4..]
+        .iter()
+        .filter(|part| !part.ends_with("|default"))
+        .count()
+}
+
 fn count_highlights(output: &str) -> usize {
     // Count the number of highlight ranges
     let ranges_line = output
@@ -359,3 +477,102 @@ const u = `outer "double" 'single' outer`;"#;
     let count = count_highlights(&output);
     assert!(count > 10, "nested strings should be highlighted");
 }
+
+#[test]
+fn fixture_disabling_strings_removes_string_faces() {
+    let code = r#"fn main() { let s = "hello"; }"#;
+
+    let with_strings = run_oneshot_highlight("rust", "catppuccin-frappe", code);
+    let without_strings = run_oneshot_highlight_strings_disabled("rust", "catppuccin-frappe", code);
+
+    let with_count = count_non_default_highlights(&with_strings);
+    let without_count = count_non_default_highlights(&without_strings);
+
+    assert!(
+        without_count < with_count,
+        "disabling [highlight] strings should drop the string literal's face: with={} without={}",
+        with_count,
+        without_count
+    );
+}
+
+#[test]
+fn fixture_rainbow_delimiters_disabled_by_default() {
+    let code = "fn main() { let v = vec![(1, 2)]; }";
+    let output = run_oneshot_highlight("rust", "catppuccin-frappe", code);
+    assert!(
+        !output.contains("giallo_rainbow_"),
+        "rainbow faces should not appear unless [rainbow] enabled = true"
+    );
+}
+
+#[test]
+fn fixture_rainbow_delimiters_enabled() {
+    let code = "fn main() { let v = vec![(1, 2)]; }";
+    let output = run_oneshot_highlight_rainbow("rust", "catppuccin-frappe", code);
+
+    assert!(
+        output.contains("set-face global giallo_rainbow_00"),
+        "enabling rainbow should define giallo_rainbow_NN faces: {}",
+        output
+    );
+    assert!(
+        output.contains("|giallo_rainbow_"),
+        "enabling rainbow should tag delimiter ranges with giallo_rainbow_*: {}",
+        output
+    );
+}
+
+#[test]
+fn fixture_semantic_modifiers_disabled_by_default() {
+    let code = "unsafe fn main() { let mut count = 0; }";
+    let output = run_oneshot_highlight("rust", "catppuccin-frappe", code);
+    assert!(
+        !output.contains("giallo_variable_mutable") && !output.contains("giallo_keyword_unsafe"),
+        "semantic modifier faces should not appear unless [semantic] enabled = true"
+    );
+}
+
+#[test]
+fn fixture_semantic_modifiers_enabled_for_declarations_and_mutable_bindings() {
+    let code = "unsafe fn main() { let mut count = 0; }";
+    let output = run_oneshot_highlight_semantic("rust", "catppuccin-frappe", code);
+
+    assert!(
+        output.contains("set-face global giallo_function_declaration"),
+        "enabling semantic modifiers should define giallo_function_declaration: {}",
+        output
+    );
+    assert!(
+        output.contains("|giallo_function_declaration"),
+        "fn main should be tagged giallo_function_declaration: {}",
+        output
+    );
+    assert!(
+        output.contains("|giallo_variable_mutable"),
+        "let mut count should be tagged giallo_variable_mutable: {}",
+        output
+    );
+    assert!(
+        output.contains("|giallo_keyword_unsafe"),
+        "the unsafe keyword should be tagged giallo_keyword_unsafe: {}",
+        output
+    );
+}
+
+#[test]
+fn fixture_semantic_modifiers_typescript_function_declaration() {
+    let code = "function main() { let count = 0; }";
+    let output = run_oneshot_highlight_semantic("typescript", "catppuccin-frappe", code);
+
+    assert!(
+        !output.contains("|giallo_function_declaration"),
+        "only Rust's `fn` keyword is recognized, not TypeScript's `function`: {}",
+        output
+    );
+    assert!(
+        !output.contains("|giallo_variable_mutable"),
+        "`let count` without `mut` is not a mutable binding: {}",
+        output
+    );
+}
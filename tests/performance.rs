@@ -3,12 +3,21 @@
 //! These tests benchmark highlighting performance across various file sizes
 //! and measure memory usage, CPU overhead, and throughput.
 
+use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
 use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
-use std::time::Instant;
-use sysinfo::{get_current_pid, System};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+use sysinfo::System;
+
+/// How often the background sampler thread polls the child process while a
+/// benchmark runs. Short enough to catch a highlight pass's transient RSS
+/// peak instead of missing it between two before/after snapshots.
+const SAMPLE_INTERVAL: Duration = Duration::from_millis(3);
 
 static COUNTER: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
 
@@ -17,12 +26,20 @@ fn get_unique_id() -> usize {
 }
 
 /// Results from a highlighting benchmark
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize)]
 pub struct BenchmarkResult {
+    pub name: String,
     pub file_lines: usize,
     pub file_bytes: usize,
     pub highlight_time_ms: f64,
-    pub memory_delta_mb: f64,
+    /// Peak resident set size the `giallo-kak` child reached while
+    /// highlighting, sampled on a background thread rather than diffed
+    /// from two snapshots (which misses transient peaks entirely).
+    pub peak_memory_mb: f64,
+    /// Accumulated CPU time (user+system) the child spent highlighting,
+    /// approximated by integrating sampled `cpu_usage()` readings over the
+    /// sampling interval.
+    pub cpu_time_ms: f64,
     pub output_size_bytes: usize,
 }
 
@@ -53,30 +70,22 @@ fn write_config(config_dir: &Path, theme: &str) {
     fs::write(&config_path, contents).expect("failed to write config");
 }
 
-/// Run a oneshot highlight and measure performance
-fn benchmark_oneshot_highlight(lang: &str, theme: &str, code: &str) -> BenchmarkResult {
+/// Run a oneshot highlight and measure performance. `name` identifies this
+/// case in exported reports (see `BenchmarkReport`) and doesn't affect the
+/// measurement itself.
+fn benchmark_oneshot_highlight(name: &str, lang: &str, theme: &str, code: &str) -> BenchmarkResult {
     let config_home = make_temp_dir("giallo-kak-perf");
     write_config(&config_home, theme);
 
     let file_lines = code.lines().count();
     let file_bytes = code.len();
 
-    // Initialize system info
-    let mut system = System::new_all();
-    system.refresh_all();
-    let pid = get_current_pid().expect("failed to get current pid");
-    let process = system.process(pid).expect("failed to get current process");
-    let memory_before = process.memory() as f64 / 1024.0 / 1024.0; // MB
-
     // Prepare input
     let payload = code.as_bytes();
     let header = format!("H {} {} {}\n", lang, theme, payload.len());
 
     let bin = env!("CARGO_BIN_EXE_giallo-kak");
 
-    // Run highlighting
-    let start = Instant::now();
-
     let mut child = Command::new(bin)
         .arg("--oneshot")
         .env("XDG_CONFIG_HOME", &config_home)
@@ -86,6 +95,34 @@ fn benchmark_oneshot_highlight(lang: &str, theme: &str, code: &str) -> Benchmark
         .spawn()
         .expect("failed to spawn giallo-kak");
 
+    let child_pid = sysinfo::Pid::from_u32(child.id());
+
+    // Sample the *child's* resource usage on a background thread for the
+    // whole run instead of diffing two snapshots of our own process, which
+    // never measured `giallo-kak` at all and would miss any transient peak
+    // between the two reads.
+    let stop = Arc::new(AtomicBool::new(false));
+    let sampler_stop = stop.clone();
+    let sampler = thread::spawn(move || {
+        let mut system = System::new_all();
+        let mut peak_memory_mb = 0.0_f64;
+        let mut cpu_time_ms = 0.0_f64;
+        while !sampler_stop.load(Ordering::Relaxed) {
+            system.refresh_process(child_pid);
+            if let Some(process) = system.process(child_pid) {
+                let memory_mb = process.memory() as f64 / 1024.0 / 1024.0;
+                peak_memory_mb = peak_memory_mb.max(memory_mb);
+                cpu_time_ms +=
+                    (process.cpu_usage() as f64 / 100.0) * SAMPLE_INTERVAL.as_millis() as f64;
+            }
+            thread::sleep(SAMPLE_INTERVAL);
+        }
+        (peak_memory_mb, cpu_time_ms)
+    });
+
+    // Run highlighting
+    let start = Instant::now();
+
     {
         let stdin = child.stdin.as_mut().expect("failed to open stdin");
         stdin
@@ -100,25 +137,24 @@ fn benchmark_oneshot_highlight(lang: &str, theme: &str, code: &str) -> Benchmark
 
     let highlight_time = start.elapsed().as_secs_f64() * 1000.0; // Convert to ms
 
+    stop.store(true, Ordering::Relaxed);
+    let (peak_memory_mb, cpu_time_ms) = sampler.join().expect("sampler thread panicked");
+
     assert!(
         output.status.success(),
         "giallo-kak failed: {}",
         String::from_utf8_lossy(&output.stderr)
     );
 
-    // Measure memory after
-    system.refresh_all();
-    let process = system.process(pid).expect("failed to get current process");
-    let memory_after = process.memory() as f64 / 1024.0 / 1024.0;
-    let memory_delta = memory_after - memory_before;
-
     let output_size = output.stdout.len();
 
     BenchmarkResult {
+        name: name.to_string(),
         file_lines,
         file_bytes,
         highlight_time_ms: highlight_time,
-        memory_delta_mb: memory_delta,
+        peak_memory_mb,
+        cpu_time_ms,
         output_size_bytes: output_size,
     }
 }
@@ -327,14 +363,14 @@ pub fn generate_python_file(lines: usize) -> String {
 #[test]
 fn perf_highlight_small_file_rust() {
     let code = generate_rust_file(100);
-    let result = benchmark_oneshot_highlight("rust", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("rust_small", "rust", "catppuccin-frappe", &code);
 
     println!(
-        "Small file ({} lines, {} bytes): {:.2}ms, memory delta: {:.2}MB, output: {} bytes",
+        "Small file ({} lines, {} bytes): {:.2}ms, peak memory: {:.2}MB, output: {} bytes",
         result.file_lines,
         result.file_bytes,
         result.highlight_time_ms,
-        result.memory_delta_mb,
+        result.peak_memory_mb,
         result.output_size_bytes
     );
 
@@ -345,25 +381,25 @@ fn perf_highlight_small_file_rust() {
         result.highlight_time_ms
     );
 
-    // Memory threshold: < 20MB delta
+    // Memory threshold: < 20MB peak RSS
     assert!(
-        result.memory_delta_mb < 20.0,
-        "Memory delta should be <20MB, was {:.2}MB",
-        result.memory_delta_mb
+        result.peak_memory_mb < 20.0,
+        "Peak memory should be <20MB, was {:.2}MB",
+        result.peak_memory_mb
     );
 }
 
 #[test]
 fn perf_highlight_medium_file_rust() {
     let code = generate_rust_file(1000);
-    let result = benchmark_oneshot_highlight("rust", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("rust_medium", "rust", "catppuccin-frappe", &code);
 
     println!(
-        "Medium file ({} lines, {} bytes): {:.2}ms, memory delta: {:.2}MB, output: {} bytes",
+        "Medium file ({} lines, {} bytes): {:.2}ms, peak memory: {:.2}MB, output: {} bytes",
         result.file_lines,
         result.file_bytes,
         result.highlight_time_ms,
-        result.memory_delta_mb,
+        result.peak_memory_mb,
         result.output_size_bytes
     );
 
@@ -374,25 +410,25 @@ fn perf_highlight_medium_file_rust() {
         result.highlight_time_ms
     );
 
-    // Memory threshold: < 50MB delta
+    // Memory threshold: < 50MB peak RSS
     assert!(
-        result.memory_delta_mb < 50.0,
-        "Memory delta should be <50MB, was {:.2}MB",
-        result.memory_delta_mb
+        result.peak_memory_mb < 50.0,
+        "Peak memory should be <50MB, was {:.2}MB",
+        result.peak_memory_mb
     );
 }
 
 #[test]
 fn perf_highlight_large_file_rust() {
     let code = generate_rust_file(10000);
-    let result = benchmark_oneshot_highlight("rust", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("rust_large", "rust", "catppuccin-frappe", &code);
 
     println!(
-        "Large file ({} lines, {} bytes): {:.2}ms, memory delta: {:.2}MB, output: {} bytes",
+        "Large file ({} lines, {} bytes): {:.2}ms, peak memory: {:.2}MB, output: {} bytes",
         result.file_lines,
         result.file_bytes,
         result.highlight_time_ms,
-        result.memory_delta_mb,
+        result.peak_memory_mb,
         result.output_size_bytes
     );
 
@@ -403,22 +439,22 @@ fn perf_highlight_large_file_rust() {
         result.highlight_time_ms
     );
 
-    // Memory threshold: < 150MB delta
+    // Memory threshold: < 150MB peak RSS
     assert!(
-        result.memory_delta_mb < 150.0,
-        "Memory delta should be <150MB, was {:.2}MB",
-        result.memory_delta_mb
+        result.peak_memory_mb < 150.0,
+        "Peak memory should be <150MB, was {:.2}MB",
+        result.peak_memory_mb
     );
 }
 
 #[test]
 fn perf_highlight_small_file_javascript() {
     let code = generate_javascript_file(100);
-    let result = benchmark_oneshot_highlight("javascript", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("javascript_small", "javascript", "catppuccin-frappe", &code);
 
     println!(
-        "JS small file ({} lines): {:.2}ms, memory delta: {:.2}MB",
-        result.file_lines, result.highlight_time_ms, result.memory_delta_mb
+        "JS small file ({} lines): {:.2}ms, peak memory: {:.2}MB",
+        result.file_lines, result.highlight_time_ms, result.peak_memory_mb
     );
 
     assert!(
@@ -431,11 +467,11 @@ fn perf_highlight_small_file_javascript() {
 #[test]
 fn perf_highlight_medium_file_javascript() {
     let code = generate_javascript_file(1000);
-    let result = benchmark_oneshot_highlight("javascript", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("javascript_medium", "javascript", "catppuccin-frappe", &code);
 
     println!(
-        "JS medium file ({} lines): {:.2}ms, memory delta: {:.2}MB",
-        result.file_lines, result.highlight_time_ms, result.memory_delta_mb
+        "JS medium file ({} lines): {:.2}ms, peak memory: {:.2}MB",
+        result.file_lines, result.highlight_time_ms, result.peak_memory_mb
     );
 
     assert!(
@@ -448,11 +484,11 @@ fn perf_highlight_medium_file_javascript() {
 #[test]
 fn perf_highlight_small_file_python() {
     let code = generate_python_file(100);
-    let result = benchmark_oneshot_highlight("python", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("python_small", "python", "catppuccin-frappe", &code);
 
     println!(
-        "Python small file ({} lines): {:.2}ms, memory delta: {:.2}MB",
-        result.file_lines, result.highlight_time_ms, result.memory_delta_mb
+        "Python small file ({} lines): {:.2}ms, peak memory: {:.2}MB",
+        result.file_lines, result.highlight_time_ms, result.peak_memory_mb
     );
 
     assert!(
@@ -465,11 +501,11 @@ fn perf_highlight_small_file_python() {
 #[test]
 fn perf_highlight_medium_file_python() {
     let code = generate_python_file(1000);
-    let result = benchmark_oneshot_highlight("python", "catppuccin-frappe", &code);
+    let result = benchmark_oneshot_highlight("python_medium", "python", "catppuccin-frappe", &code);
 
     println!(
-        "Python medium file ({} lines): {:.2}ms, memory delta: {:.2}MB",
-        result.file_lines, result.highlight_time_ms, result.memory_delta_mb
+        "Python medium file ({} lines): {:.2}ms, peak memory: {:.2}MB",
+        result.file_lines, result.highlight_time_ms, result.peak_memory_mb
     );
 
     assert!(
@@ -498,7 +534,7 @@ fn perf_compare_themes() {
     println!("{}", "-".repeat(50));
 
     for theme in themes {
-        let result = benchmark_oneshot_highlight("rust", theme, &code);
+        let result = benchmark_oneshot_highlight(&format!("theme_compare_{}", theme), "rust", theme, &code);
         println!(
             "{:<20} {:>12.2} {:>15}",
             theme, result.highlight_time_ms, result.output_size_bytes
@@ -523,10 +559,10 @@ fn perf_compare_languages() {
     println!("{}", "-".repeat(60));
 
     for (lang, code) in languages {
-        let result = benchmark_oneshot_highlight(lang, "catppuccin-frappe", &code);
+        let result = benchmark_oneshot_highlight(&format!("lang_compare_{}", lang), lang, "catppuccin-frappe", &code);
         println!(
             "{:<15} {:>12.2} {:>15} {:>15.2}",
-            lang, result.highlight_time_ms, result.output_size_bytes, result.memory_delta_mb
+            lang, result.highlight_time_ms, result.output_size_bytes, result.peak_memory_mb
         );
     }
 }
@@ -681,13 +717,13 @@ pub fn closure_example() -> impl Fn(i32) -> i32 {
 }
 "#;
 
-    let result = benchmark_oneshot_highlight("rust", "catppuccin-frappe", complex_rust);
+    let result = benchmark_oneshot_highlight("rust_realistic", "rust", "catppuccin-frappe", complex_rust);
 
     println!(
-        "\nRealistic complex code ({} lines): {:.2}ms, memory delta: {:.2}MB",
+        "\nRealistic complex code ({} lines): {:.2}ms, peak memory: {:.2}MB",
         complex_rust.lines().count(),
         result.highlight_time_ms,
-        result.memory_delta_mb
+        result.peak_memory_mb
     );
 
     assert!(
@@ -697,6 +733,207 @@ pub fn closure_example() -> impl Fn(i32) -> i32 {
     );
 }
 
+/// A single synthetic single-line edit: replace the bytes in
+/// `[byte_offset, byte_offset + delete_len)` with `insert_text`.
+/// `description` names the edit kind for reporting, mirroring what an
+/// editor would actually send on a keystroke (`insert a token`, `delete a
+/// char`, `split a line`).
+#[derive(Debug, Clone)]
+pub struct SyntheticEdit {
+    pub description: &'static str,
+    pub byte_offset: usize,
+    pub delete_len: usize,
+    pub insert_text: String,
+}
+
+/// Generate `count` synthetic single-line edits against `code`, cycling
+/// through inserting a token, deleting a character, and splitting a line —
+/// the kinds of tiny changes an editor sends on every keystroke, as opposed
+/// to the cold full-buffer highlight every other `perf_*` test measures.
+pub fn generate_edit_sequence(code: &str, count: usize) -> Vec<SyntheticEdit> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(
+            code.char_indices()
+                .filter(|&(_, c)| c == '\n')
+                .map(|(i, _)| i + 1),
+        )
+        .collect();
+    let num_lines = line_starts.len().max(1);
+
+    (0..count)
+        .map(|i| {
+            let line_idx = i % num_lines;
+            let line_start = line_starts[line_idx];
+            let line_end = code[line_start..]
+                .find('\n')
+                .map(|rel| line_start + rel)
+                .unwrap_or(code.len());
+            let line_len = line_end - line_start;
+
+            match i % 3 {
+                0 => SyntheticEdit {
+                    description: "insert a token",
+                    byte_offset: line_end,
+                    delete_len: 0,
+                    insert_text: format!(" x{}", i),
+                },
+                1 => SyntheticEdit {
+                    description: "delete a char",
+                    byte_offset: if line_len > 0 { line_end - 1 } else { line_end },
+                    delete_len: if line_len > 0 { 1 } else { 0 },
+                    insert_text: String::new(),
+                },
+                _ => SyntheticEdit {
+                    description: "split a line",
+                    byte_offset: line_start + line_len / 2,
+                    delete_len: 0,
+                    insert_text: "\n".to_string(),
+                },
+            }
+        })
+        .collect()
+}
+
+/// Apply one synthetic edit to `code`, returning the new buffer content.
+fn apply_edit(code: &str, edit: &SyntheticEdit) -> String {
+    let mut result = String::with_capacity(code.len() + edit.insert_text.len());
+    result.push_str(&code[..edit.byte_offset]);
+    result.push_str(&edit.insert_text);
+    result.push_str(&code[edit.byte_offset + edit.delete_len..]);
+    result
+}
+
+/// BLOCKED: Yukaii/giallo.kak#chunk4-3 ("protocol edit message + tree-sitter
+/// `InputEdit` so the engine reparses only the dirty subtree, an order of
+/// magnitude faster than a full highlight") is not implemented, and the
+/// assertion below does not stand in for it — it tolerates the per-edit cost
+/// staying *within 2x* of a full re-highlight, which is the opposite of "an
+/// order of magnitude faster". Do not read a pass here as the request being
+/// satisfied.
+///
+/// `giallo::Registry::highlight` (the only entry point this crate's
+/// dependency exposes) always does a full parse — there's no `InputEdit`/
+/// dirty-subtree reparse API to plug into, and `--oneshot` spawns a fresh
+/// process per call with no tree to incrementally edit in the first place.
+/// Wiring up real incremental reparsing would mean adding that API to the
+/// `giallo` crate itself, which lives outside this repository; until it
+/// does, there is no protocol edit message to add on this side either — a
+/// wire command that claims to be incremental while secretly doing a full
+/// re-parse would be worse than not having one. This test instead measures
+/// the per-edit cost of the only highlighting path that actually exists
+/// today, as a regression guard and as the number a real incremental mode
+/// would need to beat.
+#[test]
+fn perf_incremental_rust() {
+    let base_code = generate_rust_file(10_000);
+    let baseline = benchmark_oneshot_highlight(
+        "incremental_baseline_full",
+        "rust",
+        "catppuccin-frappe",
+        &base_code,
+    );
+
+    let edits = generate_edit_sequence(&base_code, 20);
+    let mut code = base_code.clone();
+    let mut edit_times_ms = Vec::with_capacity(edits.len());
+
+    for (i, edit) in edits.iter().enumerate() {
+        code = apply_edit(&code, edit);
+        let result = benchmark_oneshot_highlight(
+            &format!("incremental_edit_{}_{}", i, edit.description),
+            "rust",
+            "catppuccin-frappe",
+            &code,
+        );
+        edit_times_ms.push(result.highlight_time_ms);
+    }
+
+    edit_times_ms.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let median_edit_ms = edit_times_ms[edit_times_ms.len() / 2];
+
+    println!(
+        "\nIncremental re-highlight over {} edits on a {}-line file:",
+        edits.len(),
+        base_code.lines().count()
+    );
+    println!("Full highlight baseline: {:.2}ms", baseline.highlight_time_ms);
+    println!(
+        "Median per-edit full re-highlight: {:.2}ms ({:.2}x baseline)",
+        median_edit_ms,
+        median_edit_ms / baseline.highlight_time_ms
+    );
+
+    // Without a real incremental reparse path, per-edit latency should
+    // still roughly track the baseline full highlight rather than blow up
+    // — this only catches a regression, it doesn't claim a speedup.
+    assert!(
+        median_edit_ms < baseline.highlight_time_ms * 2.0,
+        "per-edit re-highlight ({:.2}ms) should stay within 2x of the full-file baseline ({:.2}ms)",
+        median_edit_ms,
+        baseline.highlight_time_ms
+    );
+}
+
+/// Coarse linear-growth guard in the spirit of rust-analyzer's
+/// `AssertLinear`: highlight synthetic files of doubling size and check that
+/// per-line cost doesn't blow up as file size grows. A real O(n^2) (or
+/// worse) regression shows up as per-line cost climbing with file size;
+/// linear/near-linear cost keeps it roughly flat. Generous tolerance because
+/// process spawn overhead is a roughly-constant cost on top of the actual
+/// parse+highlight, and disproportionately inflates the smallest sample's
+/// per-line cost, which would otherwise make even perfectly linear scaling
+/// look sub-linear by comparison.
+///
+/// BLOCKED: Yukaii/giallo.kak#chunk9-5 ("incremental re-highlighting wired
+/// through a new protocol verb with a per-buffer cached `Tree`, tested
+/// against an edit that spans an injection boundary") is not implemented by
+/// this test or anywhere else in this commit. No protocol verb, no cached
+/// `Tree`, and no per-buffer incremental path exist in `src/main.rs` — this
+/// is a full-reparse linear-growth regression guard only, same BLOCKED
+/// status and reasoning as `perf_incremental_rust` above (no `InputEdit` API
+/// reaches this crate from `giallo::Registry`). An edit spanning a
+/// tree-sitter injection boundary, the edge case the request also asks for,
+/// isn't benchmarkable either: injection support is itself BLOCKED (see the
+/// comment above `DEFAULT_THEME` in `src/main.rs`, Yukaii/giallo.kak#chunk9-1),
+/// so there is no injection boundary in this binary to span. Passing does
+/// not mean chunk9-5 is done.
+#[test]
+fn perf_highlight_time_scales_linearly() {
+    let sizes = [500usize, 1000, 2000, 4000];
+    let mut per_line_costs = Vec::with_capacity(sizes.len());
+
+    for &size in &sizes {
+        let code = generate_rust_file(size);
+        let result = benchmark_oneshot_highlight(
+            &format!("linear_scan_{}", size),
+            "rust",
+            "catppuccin-frappe",
+            &code,
+        );
+        let per_line_ms = result.highlight_time_ms / result.file_lines as f64;
+        println!(
+            "{} lines: {:.2}ms total, {:.4}ms/line",
+            result.file_lines, result.highlight_time_ms, per_line_ms
+        );
+        per_line_costs.push(per_line_ms);
+    }
+
+    let smallest = per_line_costs[0];
+    let largest = *per_line_costs.last().unwrap();
+
+    // A quadratic regression would show per-line cost growing roughly
+    // proportionally with file size (8x here, since sizes.last()/sizes[0]
+    // == 8); linear cost keeps per-line cost flat. Allow generous headroom
+    // (4x) for noise and spawn-overhead skew.
+    assert!(
+        largest < smallest * 4.0,
+        "per-line highlight cost grew {:.2}x ({:.4}ms/line -> {:.4}ms/line) across an 8x size increase — looks like a superlinear regression",
+        largest / smallest,
+        smallest,
+        largest
+    );
+}
+
 #[test]
 fn perf_throughput_multiple_updates() {
     // Test throughput with multiple rapid updates
@@ -706,7 +943,7 @@ fn perf_throughput_multiple_updates() {
     let start = Instant::now();
 
     for _ in 0..iterations {
-        let _result = benchmark_oneshot_highlight("rust", "catppuccin-frappe", &code);
+        let _result = benchmark_oneshot_highlight("rust_throughput", "rust", "catppuccin-frappe", &code);
     }
 
     let total_time = start.elapsed().as_secs_f64() * 1000.0;
@@ -730,3 +967,507 @@ fn perf_throughput_multiple_updates() {
         throughput
     );
 }
+
+/// A byte-size bucket a corpus file falls into, for grouping throughput
+/// numbers the way real editors see file sizes in practice rather than the
+/// handful of fixed `small`/`medium`/`large` line counts the synthetic
+/// generators use.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SizeBucket {
+    Small,  // < 4 KiB
+    Medium, // < 64 KiB
+    Large,  // >= 64 KiB
+}
+
+impl SizeBucket {
+    fn for_bytes(bytes: usize) -> Self {
+        if bytes < 4 * 1024 {
+            SizeBucket::Small
+        } else if bytes < 64 * 1024 {
+            SizeBucket::Medium
+        } else {
+            SizeBucket::Large
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            SizeBucket::Small => "small",
+            SizeBucket::Medium => "medium",
+            SizeBucket::Large => "large",
+        }
+    }
+}
+
+/// Map a file extension to the `giallo` language name `benchmark_oneshot_highlight`
+/// expects, or `None` for extensions the corpus walker should skip.
+fn corpus_language_for_extension(ext: &str) -> Option<&'static str> {
+    match ext {
+        "rs" => Some("rust"),
+        "js" | "mjs" | "cjs" => Some("javascript"),
+        "py" => Some("python"),
+        _ => None,
+    }
+}
+
+/// Recursively collect `(path, language, bytes)` for every file under `dir`
+/// whose extension `corpus_language_for_extension` recognizes. There's no
+/// `walkdir` dependency in this crate, so this walks by hand with a small
+/// explicit stack rather than recursion, same as any other directory-walk
+/// already in this codebase (see `CRAWL` handling in `src/main.rs`).
+fn collect_corpus_files(dir: &Path) -> Vec<(PathBuf, &'static str, usize)> {
+    let mut files = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+
+    while let Some(current) = stack.pop() {
+        let Ok(entries) = fs::read_dir(&current) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            if path.is_dir() {
+                stack.push(path);
+                continue;
+            }
+            let Some(ext) = path.extension().and_then(|e| e.to_str()) else {
+                continue;
+            };
+            let Some(lang) = corpus_language_for_extension(ext) else {
+                continue;
+            };
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            files.push((path, lang, metadata.len() as usize));
+        }
+    }
+
+    files
+}
+
+/// Benchmark every real-world source file under `GIALLO_PERF_CORPUS_DIR`
+/// (set this to, e.g., a local clone of a large Rust/JS/Python project to
+/// profile giallo against your own code), bucketed by file size, and report
+/// throughput in MB/s and lines/s per language/bucket. The synthetic
+/// `generate_*_file` helpers produce uniform token distributions that
+/// under-exercise pathological real code (deep generics, macro-heavy
+/// files, long strings, minified JS); this test complements them with
+/// whatever real files the caller points it at instead of shipping a
+/// vendored corpus into the repository.
+///
+/// No-op (and no assertions) unless `GIALLO_PERF_CORPUS_DIR` is set, since
+/// this repository doesn't vendor a sample corpus itself.
+#[test]
+fn perf_corpus_benchmark() {
+    let Ok(corpus_dir) = std::env::var("GIALLO_PERF_CORPUS_DIR") else {
+        return;
+    };
+    let corpus_dir = PathBuf::from(corpus_dir);
+    let files = collect_corpus_files(&corpus_dir);
+    if files.is_empty() {
+        println!(
+            "No recognized source files found under {}; skipping corpus benchmark",
+            corpus_dir.display()
+        );
+        return;
+    }
+
+    let mut buckets: std::collections::BTreeMap<(&'static str, SizeBucket), Vec<BenchmarkResult>> =
+        std::collections::BTreeMap::new();
+
+    for (path, lang, bytes) in &files {
+        let Ok(code) = fs::read_to_string(path) else {
+            continue;
+        };
+        let bucket = SizeBucket::for_bytes(*bytes);
+        let name = format!("corpus_{}_{}_{}", lang, bucket.label(), buckets.len());
+        let result = benchmark_oneshot_highlight(&name, lang, "catppuccin-frappe", &code);
+        buckets
+            .entry((lang, bucket))
+            .or_default()
+            .push(result);
+    }
+
+    println!("\nCorpus benchmark: {} files under {}", files.len(), corpus_dir.display());
+    for ((lang, bucket), results) in &buckets {
+        let total_bytes: usize = results.iter().map(|r| r.file_bytes).sum();
+        let total_lines: usize = results.iter().map(|r| r.file_lines).sum();
+        let total_ms: f64 = results.iter().map(|r| r.highlight_time_ms).sum();
+        let mb_per_sec = (total_bytes as f64 / (1024.0 * 1024.0)) / (total_ms / 1000.0);
+        let lines_per_sec = total_lines as f64 / (total_ms / 1000.0);
+        println!(
+            "  {}/{}: {} files, {:.2} MB/s, {:.0} lines/s",
+            lang,
+            bucket.label(),
+            results.len(),
+            mb_per_sec,
+            lines_per_sec
+        );
+    }
+}
+
+/// Which shape to write an exported benchmark report in (the path comes
+/// from `GIALLO_BENCH_REPORT`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ReportFormat {
+    Json,
+    Junit,
+}
+
+impl ReportFormat {
+    /// Read `GIALLO_BENCH_FORMAT` (`json` or `junit`); `None` if unset or
+    /// unrecognized, meaning the caller should skip reporting entirely.
+    fn from_env() -> Option<Self> {
+        match std::env::var("GIALLO_BENCH_FORMAT").ok()?.as_str() {
+            "json" => Some(ReportFormat::Json),
+            "junit" => Some(ReportFormat::Junit),
+            _ => None,
+        }
+    }
+}
+
+/// A growable set of benchmark results that can be exported as JSON or
+/// JUnit, so CI can track highlighting performance over time instead of
+/// only reading `println!` output from ad-hoc `perf_*` tests.
+#[derive(Debug, Default)]
+struct BenchmarkReport {
+    results: Vec<BenchmarkResult>,
+}
+
+impl BenchmarkReport {
+    fn record(&mut self, result: BenchmarkResult) {
+        self.results.push(result);
+    }
+
+    /// Write one JSON object per benchmark plus a trailing summary object,
+    /// newline-delimited the way libtest's `--format json` streams events.
+    fn write_json(&self, path: &Path) {
+        let mut out = String::new();
+        for result in &self.results {
+            let event = serde_json::json!({
+                "type": "bench",
+                "name": result.name,
+                "median": result.highlight_time_ms,
+                "unit": "ms",
+                "file_lines": result.file_lines,
+                "file_bytes": result.file_bytes,
+                "peak_memory_mb": result.peak_memory_mb,
+                "cpu_time_ms": result.cpu_time_ms,
+                "output_size_bytes": result.output_size_bytes,
+            });
+            out.push_str(&event.to_string());
+            out.push('\n');
+        }
+        let summary = serde_json::json!({
+            "type": "summary",
+            "total": self.results.len(),
+            "median_ms": median(self.results.iter().map(|r| r.highlight_time_ms)),
+        });
+        out.push_str(&summary.to_string());
+        out.push('\n');
+        fs::write(path, out).expect("failed to write JSON benchmark report");
+    }
+
+    /// Write a JUnit XML document with one `<testcase>` per benchmark so
+    /// existing CI dashboards that already ingest JUnit can chart giallo's
+    /// perf numbers directly.
+    fn write_junit(&self, path: &Path) {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        xml.push_str(&format!(
+            "<testsuite name=\"giallo-benchmarks\" tests=\"{}\">\n",
+            self.results.len()
+        ));
+        for result in &self.results {
+            xml.push_str(&format!(
+                "  <testcase name=\"{}\" time=\"{:.6}\">\n",
+                xml_escape(&result.name),
+                result.highlight_time_ms / 1000.0
+            ));
+            xml.push_str(&format!(
+                "    <system-out>file_lines={} file_bytes={} peak_memory_mb={:.3} cpu_time_ms={:.3} output_size_bytes={}</system-out>\n",
+                result.file_lines, result.file_bytes, result.peak_memory_mb, result.cpu_time_ms, result.output_size_bytes
+            ));
+            xml.push_str("  </testcase>\n");
+        }
+        xml.push_str("</testsuite>\n");
+        fs::write(path, xml).expect("failed to write JUnit benchmark report");
+    }
+}
+
+fn median(values: impl Iterator<Item = f64>) -> f64 {
+    let mut values: Vec<f64> = values.collect();
+    if values.is_empty() {
+        return 0.0;
+    }
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    let mid = values.len() / 2;
+    if values.len() % 2 == 0 {
+        (values[mid - 1] + values[mid]) / 2.0
+    } else {
+        values[mid]
+    }
+}
+
+fn xml_escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Exports a small benchmark corpus to `GIALLO_BENCH_REPORT` in the format
+/// named by `GIALLO_BENCH_FORMAT` (`json` or `junit`). No-op unless both are
+/// set, so this doesn't change output for a plain `cargo test` run; CI sets
+/// both to collect a diffable report alongside the pass/fail result.
+#[test]
+fn perf_export_benchmark_report() {
+    let Some(format) = ReportFormat::from_env() else {
+        return;
+    };
+    let Ok(report_path) = std::env::var("GIALLO_BENCH_REPORT") else {
+        return;
+    };
+
+    let mut report = BenchmarkReport::default();
+    report.record(benchmark_oneshot_highlight(
+        "rust_small",
+        "rust",
+        "catppuccin-frappe",
+        &generate_rust_file(100),
+    ));
+    report.record(benchmark_oneshot_highlight(
+        "javascript_small",
+        "javascript",
+        "catppuccin-frappe",
+        &generate_javascript_file(100),
+    ));
+    report.record(benchmark_oneshot_highlight(
+        "python_small",
+        "python",
+        "catppuccin-frappe",
+        &generate_python_file(100),
+    ));
+
+    let path = Path::new(&report_path);
+    match format {
+        ReportFormat::Json => report.write_json(path),
+        ReportFormat::Junit => report.write_junit(path),
+    }
+}
+
+/// Deterministic xorshift64 PRNG used only to seed-shuffle benchmark case
+/// order so repeated runs don't always warm the OS/filesystem cache in the
+/// same sequence. No `rand` dependency exists anywhere in this crate, and a
+/// reproducible seed (rather than real randomness) is exactly what letting
+/// a reviewer reproduce a reported regression requires.
+struct Xorshift64(u64);
+
+impl Xorshift64 {
+    fn new(seed: u64) -> Self {
+        Xorshift64(seed.max(1))
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        let mut x = self.0;
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        self.0 = x;
+        x
+    }
+}
+
+/// Fisher-Yates shuffle of `items`, seeded so `--shuffle`-equivalent runs
+/// (via `GIALLO_BENCH_SEED`) are reproducible rather than truly random.
+fn shuffle_seeded<T>(items: &mut [T], seed: u64) {
+    let mut rng = Xorshift64::new(seed);
+    for i in (1..items.len()).rev() {
+        let j = (rng.next_u64() as usize) % (i + 1);
+        items.swap(i, j);
+    }
+}
+
+/// One case in the regression suite: a named, fully-materialized highlight
+/// job. Stored as owned data (rather than a generator fn pointer) so the
+/// case list can be shuffled freely before running.
+struct BenchCase {
+    name: &'static str,
+    lang: &'static str,
+    theme: &'static str,
+    code: String,
+}
+
+/// Run `case` for `warmup + iterations` total passes, discarding the
+/// warmup passes, and fold the measured passes down to their median —
+/// tempering the run-to-run variance a single sample (what every other
+/// `perf_*` test in this file reports) is prone to.
+fn run_benchmark_median(case: &BenchCase, warmup: usize, iterations: usize) -> BenchmarkResult {
+    for _ in 0..warmup {
+        benchmark_oneshot_highlight(case.name, case.lang, case.theme, &case.code);
+    }
+
+    let mut samples: Vec<BenchmarkResult> = (0..iterations)
+        .map(|_| benchmark_oneshot_highlight(case.name, case.lang, case.theme, &case.code))
+        .collect();
+    samples.sort_by(|a, b| a.highlight_time_ms.partial_cmp(&b.highlight_time_ms).unwrap());
+    let mid = samples.len() / 2;
+
+    BenchmarkResult {
+        name: case.name.to_string(),
+        file_lines: samples[mid].file_lines,
+        file_bytes: samples[mid].file_bytes,
+        highlight_time_ms: median(samples.iter().map(|s| s.highlight_time_ms)),
+        peak_memory_mb: median(samples.iter().map(|s| s.peak_memory_mb)),
+        cpu_time_ms: median(samples.iter().map(|s| s.cpu_time_ms)),
+        output_size_bytes: samples[mid].output_size_bytes,
+    }
+}
+
+/// A baselined case's timing/memory, as stored in the JSON baseline file —
+/// just the two fields the regression gate actually compares against.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct BaselineEntry {
+    highlight_time_ms: f64,
+    peak_memory_mb: f64,
+}
+
+/// `name -> BaselineEntry` for every case in a blessed baseline run.
+type Baseline = std::collections::BTreeMap<String, BaselineEntry>;
+
+fn load_baseline(path: &Path) -> Option<Baseline> {
+    let contents = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&contents).ok()
+}
+
+fn save_baseline(path: &Path, baseline: &Baseline) {
+    let contents = serde_json::to_string_pretty(baseline).expect("failed to serialize baseline");
+    fs::write(path, contents).expect("failed to write baseline file");
+}
+
+/// Regression suite: runs a small fixed corpus of cases (optionally
+/// shuffled via `GIALLO_BENCH_SEED`) with warmup + measured iterations,
+/// then either blesses a new baseline (`GIALLO_BENCH_BLESS=1`) or compares
+/// medians against the stored one and fails on regression.
+///
+/// Env vars (all optional; the whole test is a no-op unless
+/// `GIALLO_BENCH_BASELINE` is set, so a plain `cargo test` run is
+/// unaffected):
+/// - `GIALLO_BENCH_BASELINE`: path to the JSON baseline file (required)
+/// - `GIALLO_BENCH_BLESS=1`: write current medians as the new baseline
+///   instead of comparing against it
+/// - `GIALLO_BENCH_SEED`: u64 seed to shuffle case order (default: no shuffle)
+/// - `GIALLO_BENCH_WARMUP`: warmup iterations per case (default: 2)
+/// - `GIALLO_BENCH_ITERATIONS`: measured iterations per case (default: 5)
+/// - `GIALLO_BENCH_TOLERANCE_TIME_PCT`: max allowed highlight_time_ms
+///   regression, in percent (default: 15.0)
+/// - `GIALLO_BENCH_TOLERANCE_MEMORY_PCT`: max allowed peak_memory_mb
+///   regression, in percent (default: 10.0)
+#[test]
+fn perf_regression_suite() {
+    let Ok(baseline_path) = std::env::var("GIALLO_BENCH_BASELINE") else {
+        return;
+    };
+    let baseline_path = PathBuf::from(baseline_path);
+
+    let warmup: usize = std::env::var("GIALLO_BENCH_WARMUP")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(2);
+    let iterations: usize = std::env::var("GIALLO_BENCH_ITERATIONS")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(5);
+    let tolerance_time_pct: f64 = std::env::var("GIALLO_BENCH_TOLERANCE_TIME_PCT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(15.0);
+    let tolerance_memory_pct: f64 = std::env::var("GIALLO_BENCH_TOLERANCE_MEMORY_PCT")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(10.0);
+    let bless = std::env::var("GIALLO_BENCH_BLESS").as_deref() == Ok("1");
+
+    let mut cases = vec![
+        BenchCase { name: "regress_rust_small", lang: "rust", theme: "catppuccin-frappe", code: generate_rust_file(100) },
+        BenchCase { name: "regress_rust_medium", lang: "rust", theme: "catppuccin-frappe", code: generate_rust_file(1000) },
+        BenchCase { name: "regress_javascript_small", lang: "javascript", theme: "catppuccin-frappe", code: generate_javascript_file(100) },
+        BenchCase { name: "regress_python_small", lang: "python", theme: "catppuccin-frappe", code: generate_python_file(100) },
+    ];
+
+    if let Some(seed) = std::env::var("GIALLO_BENCH_SEED").ok().and_then(|v| v.parse::<u64>().ok()) {
+        shuffle_seeded(&mut cases, seed);
+    }
+
+    let mut current = Baseline::new();
+    for case in &cases {
+        let result = run_benchmark_median(case, warmup, iterations);
+        current.insert(
+            case.name.to_string(),
+            BaselineEntry {
+                highlight_time_ms: result.highlight_time_ms,
+                peak_memory_mb: result.peak_memory_mb,
+            },
+        );
+    }
+
+    if bless {
+        save_baseline(&baseline_path, &current);
+        println!("Blessed new baseline at {}", baseline_path.display());
+        return;
+    }
+
+    let Some(previous) = load_baseline(&baseline_path) else {
+        save_baseline(&baseline_path, &current);
+        println!(
+            "No baseline found at {}; wrote the current run as the initial baseline",
+            baseline_path.display()
+        );
+        return;
+    };
+
+    println!(
+        "\n{:<28} {:>12} {:>12} {:>8} {:>12} {:>12} {:>8}",
+        "case", "base_ms", "cur_ms", "Δ%", "base_mb", "cur_mb", "Δ%"
+    );
+    let mut regressions = Vec::new();
+    for case in &cases {
+        let cur = &current[case.name];
+        let Some(base) = previous.get(case.name) else {
+            println!("{:<28} {:>12}", case.name, "(new case, no baseline)");
+            continue;
+        };
+
+        let time_delta_pct = (cur.highlight_time_ms - base.highlight_time_ms) / base.highlight_time_ms * 100.0;
+        let memory_delta_pct = (cur.peak_memory_mb - base.peak_memory_mb) / base.peak_memory_mb.max(0.001) * 100.0;
+        println!(
+            "{:<28} {:>12.2} {:>12.2} {:>7.1}% {:>12.2} {:>12.2} {:>7.1}%",
+            case.name,
+            base.highlight_time_ms,
+            cur.highlight_time_ms,
+            time_delta_pct,
+            base.peak_memory_mb,
+            cur.peak_memory_mb,
+            memory_delta_pct
+        );
+
+        if time_delta_pct > tolerance_time_pct {
+            regressions.push(format!(
+                "{}: highlight_time_ms regressed {:.1}% (tolerance {:.1}%)",
+                case.name, time_delta_pct, tolerance_time_pct
+            ));
+        }
+        if memory_delta_pct > tolerance_memory_pct {
+            regressions.push(format!(
+                "{}: peak_memory_mb regressed {:.1}% (tolerance {:.1}%)",
+                case.name, memory_delta_pct, tolerance_memory_pct
+            ));
+        }
+    }
+
+    assert!(
+        regressions.is_empty(),
+        "performance regression(s) detected:\n{}",
+        regressions.join("\n")
+    );
+}